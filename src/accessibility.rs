@@ -0,0 +1,108 @@
+use crate::{
+    loading::{MaterialsAssets, ThemeMaterials},
+    settings::{BrickPalette, Settings},
+};
+use bevy::prelude::*;
+
+/// Applies the accessibility settings that aren't tied to a part of the game:
+/// the high contrast materials and the text scale
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(apply_contrast.system())
+            .add_system(scale_text.system());
+    }
+}
+
+impl BrickPalette {
+    /// Tint of a brick with `life` hits left after the next one, `None` leaves the theme's frame as is.
+    /// The colors also differ in lightness, to tell them apart in grayscale.
+    pub fn color(self, life: u32) -> Option<Color> {
+        let colors: &[Color] = match self {
+            BrickPalette::Theme => return None,
+            // Okabe-Ito: orange, sky blue, blue, yellow, reddish purple
+            BrickPalette::RedGreen => &[
+                Color::rgb(0.90, 0.62, 0.0),
+                Color::rgb(0.34, 0.71, 0.91),
+                Color::rgb(0.0, 0.45, 0.70),
+                Color::rgb(0.94, 0.89, 0.26),
+                Color::rgb(0.80, 0.47, 0.65),
+            ],
+            // red, teal, white, pink, dark gray
+            BrickPalette::BlueYellow => &[
+                Color::rgb(0.86, 0.15, 0.16),
+                Color::rgb(0.0, 0.62, 0.62),
+                Color::rgb(1.0, 1.0, 1.0),
+                Color::rgb(1.0, 0.6, 0.8),
+                Color::rgb(0.4, 0.4, 0.4),
+            ],
+        };
+        Some(colors[(life as usize).min(colors.len() - 1)])
+    }
+}
+
+/// A text that has to fit in something, e.g. a brick, and isn't scaled with the UI text
+pub struct FixedTextSize;
+
+/// Font size of each section of a text as it was spawned, before the text scale
+struct BaseFontSizes(Vec<f32>);
+
+fn apply_contrast(
+    settings: Res<Settings>,
+    materials_assets: Option<Res<MaterialsAssets>>,
+    theme_materials: Option<Res<ThemeMaterials>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let (handles, theme) = match (materials_assets, theme_materials) {
+        (Some(handles), Some(theme)) => (handles, theme),
+        _ => return,
+    };
+    // the materials are added again each time a theme is loaded
+    if !settings.is_changed() && !handles.is_changed() {
+        return;
+    }
+
+    let targets = [
+        (&handles.ball, &theme.ball, Color::WHITE),
+        (&handles.paddle, &theme.paddle, Color::rgb(1.0, 0.85, 0.0)),
+        (&handles.wall, &theme.wall, Color::rgb(0.85, 0.85, 0.85)),
+        (&handles.laser, &theme.laser, Color::CYAN),
+    ];
+    for (handle, theme_material, color) in targets.iter() {
+        if let Some(material) = materials.get_mut(*handle) {
+            *material = if settings.high_contrast {
+                ColorMaterial::color(*color)
+            } else {
+                theme_material.material()
+            };
+        }
+    }
+}
+
+type ScalableText<'a> = (Entity, &'a mut Text, Option<&'a BaseFontSizes>);
+
+fn scale_text(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut text_query: Query<ScalableText, Without<FixedTextSize>>,
+) {
+    for (entity, mut text, base) in text_query.iter_mut() {
+        let base = match base {
+            Some(_) if !settings.is_changed() => continue,
+            Some(base) => base.0.clone(),
+            None => {
+                let base: Vec<f32> = text
+                    .sections
+                    .iter()
+                    .map(|section| section.style.font_size)
+                    .collect();
+                commands.entity(entity).insert(BaseFontSizes(base.clone()));
+                base
+            }
+        };
+        for (section, size) in text.sections.iter_mut().zip(base) {
+            section.style.font_size = size * settings.text_scale;
+        }
+    }
+}
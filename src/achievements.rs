@@ -0,0 +1,397 @@
+use crate::{
+    localization::{Locale, Localized},
+    menu::{ButtonMaterials, MenuBuilder, MenuInput},
+    ron_asset::{RonAsset, RonAssetLoader},
+    settings::Settings,
+    GameState,
+};
+use bevy::{prelude::*, reflect::TypeUuid};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs};
+
+/// Relative to the working directory, like the settings
+const ACHIEVEMENTS_PATH: &str = "achievements.ron";
+
+/// The achievements defined in `assets/game.achievements.ron`: unlocked during the game,
+/// saved locally, announced with a toast and listed in their own screen from the menu
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<AchievementList>()
+            .init_asset_loader::<RonAssetLoader<AchievementList>>()
+            .insert_resource(UnlockedAchievements::load())
+            .add_event::<AchievementUnlocked>()
+            .add_startup_system(setup_toasts.system())
+            .add_system(show_toasts.system())
+            .add_system(fade_toasts.system())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Achievements).with_system(setup_screen.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Achievements)
+                    .after("menu_navigation")
+                    .with_system(screen_actions.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Achievements).with_system(cleanup.system()),
+            );
+    }
+}
+
+#[derive(Deserialize, TypeUuid, Clone)]
+#[uuid = "8d1f6b2e-4c3a-4f7d-b5e9-2a6c0e8d4f17"]
+pub struct AchievementList {
+    /// in the order they are listed
+    pub achievements: Vec<Achievement>,
+}
+
+impl RonAsset for AchievementList {
+    const EXTENSIONS: &'static [&'static str] = &["achievements.ron"];
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Achievement {
+    /// its strings in the locale files are `achievement.<id>` and `achievement.<id>.description`
+    pub id: String,
+    pub condition: Condition,
+}
+
+impl Achievement {
+    pub fn name(&self) -> Localized {
+        Localized::key(format!("achievement.{}", self.id))
+    }
+
+    pub fn description(&self) -> Localized {
+        Localized::key(format!("achievement.{}.description", self.id))
+    }
+}
+
+/// What unlocks an achievement, checked by the game as it's played
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Condition {
+    /// levels cleared in a single game
+    LevelsCleared(u32),
+    /// a level cleared without losing a ball
+    FlawlessLevel,
+    /// a ball going at least this fast
+    BallSpeed(f32),
+    /// this many balls in play at once
+    BallsInPlay(usize),
+    /// bricks destroyed by one ball between two bounces on the paddle
+    Chain(u32),
+    /// paddle hits without losing a ball
+    Rally(u32),
+    /// in a single game
+    Score(u32),
+}
+
+/// Ids of the achievements unlocked so far, saved along the settings
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct UnlockedAchievements {
+    ids: BTreeSet<String>,
+}
+
+impl UnlockedAchievements {
+    /// The saved achievements, none when there are none yet or they can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(ACHIEVEMENTS_PATH)
+            .ok()
+            .and_then(|unlocked| ron::de::from_str(&unlocked).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::new())
+            .map_err(anyhow::Error::from)
+            .and_then(|unlocked| {
+                fs::write(ACHIEVEMENTS_PATH, unlocked).map_err(anyhow::Error::from)
+            });
+        match result {
+            Ok(()) => debug!(path = ACHIEVEMENTS_PATH, "achievements saved"),
+            Err(error) => warn!(path = ACHIEVEMENTS_PATH, %error, "failed to save achievements"),
+        }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Unlocks the achievement and saves it, `false` if it already was
+    pub fn unlock(&mut self, id: &str) -> bool {
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+        info!(id, "achievement unlocked");
+        self.save();
+        true
+    }
+}
+
+/// Sent the first time an achievement is unlocked
+pub struct AchievementUnlocked {
+    pub achievement: Achievement,
+}
+
+const TOAST_SECONDS: f32 = 3.0;
+const TOAST_FADE_SECONDS: f32 = 0.3;
+const TOAST_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+
+/// Column of toasts in the top right corner, on top of every screen
+struct ToastStack;
+
+struct Toast {
+    /// its own, so it can fade without affecting the other toasts
+    background: Handle<ColorMaterial>,
+    timer: Timer,
+}
+
+fn setup_toasts(mut commands: Commands, button_materials: Res<ButtonMaterials>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexEnd,
+                ..Default::default()
+            },
+            material: button_materials.layout.clone(),
+            ..Default::default()
+        })
+        .insert(ToastStack);
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut unlocked_events: EventReader<AchievementUnlocked>,
+    stack_query: Query<Entity, With<ToastStack>>,
+) {
+    let stack = match stack_query.single() {
+        Ok(stack) => stack,
+        Err(_) => return,
+    };
+
+    for unlocked in unlocked_events.iter() {
+        let background = materials.add(Color::NONE.into());
+        // transparent until the first fade step
+        let text = |parent: &mut ChildBuilder, value: Localized, font_size: f32, color: Color| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        locale.text(&value),
+                        TextStyle {
+                            font: locale.font.clone(),
+                            font_size,
+                            color,
+                        },
+                        Default::default(),
+                    ),
+                    ..Default::default()
+                })
+                .insert(value);
+        };
+        let toast = commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    margin: Rect::all(Val::Px(5.0)),
+                    padding: Rect::all(Val::Px(10.0)),
+                    flex_direction: FlexDirection::ColumnReverse,
+                    ..Default::default()
+                },
+                material: background.clone(),
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                text(
+                    parent,
+                    "achievements.unlocked".into(),
+                    16.0,
+                    Color::rgba(0.8, 0.8, 0.8, 0.0),
+                );
+                text(
+                    parent,
+                    unlocked.achievement.name(),
+                    22.0,
+                    Color::rgba(1.0, 0.65, 0.0, 0.0),
+                );
+            })
+            .insert(Toast {
+                background,
+                timer: Timer::from_seconds(TOAST_SECONDS, false),
+            })
+            .id();
+        commands.entity(stack).push_children(&[toast]);
+    }
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut toast_query: Query<(Entity, &mut Toast, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut toast, children) in toast_query.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // shown and hidden at once with reduced motion
+        let alpha = if settings.reduced_motion {
+            1.0
+        } else {
+            let elapsed = toast.timer.elapsed_secs();
+            let left = TOAST_SECONDS - elapsed;
+            (elapsed.min(left) / TOAST_FADE_SECONDS).min(1.0)
+        };
+        if let Some(material) = materials.get_mut(&toast.background) {
+            let mut color = TOAST_BACKGROUND;
+            color.set_a(TOAST_BACKGROUND.a() * alpha);
+            material.color = color;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+struct AchievementsScreen;
+
+enum AchievementsButton {
+    Back,
+}
+
+fn setup_screen(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    button_materials: Res<ButtonMaterials>,
+    list: Res<AchievementList>,
+    unlocked: Res<UnlockedAchievements>,
+) {
+    let menu = MenuBuilder::new(Size::new(Val::Px(250.0), Val::Px(50.0)))
+        .style(Style {
+            margin: Rect::all(Val::Px(10.0)),
+            ..Default::default()
+        })
+        .button(AchievementsButton::Back, "common.back")
+        .spawn(&mut commands, &locale, &button_materials)
+        .id();
+
+    let text = |parent: &mut ChildBuilder, value: Localized, font_size: f32, color: Color| {
+        parent
+            .spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    locale.text(&value),
+                    TextStyle {
+                        font: locale.font.clone(),
+                        font_size,
+                        color,
+                    },
+                    TextAlignment {
+                        horizontal: HorizontalAlign::Center,
+                        vertical: VerticalAlign::Center,
+                    },
+                ),
+                ..Default::default()
+            })
+            .insert(value);
+    };
+    let count = list
+        .achievements
+        .iter()
+        .filter(|achievement| unlocked.contains(&achievement.id))
+        .count();
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: button_materials.layout.clone(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            text(parent, "achievements.title".into(), 48.0, Color::ORANGE);
+            text(
+                parent,
+                Localized::from("achievements.progress")
+                    .arg("unlocked", count.to_string())
+                    .arg("total", list.achievements.len().to_string()),
+                20.0,
+                Color::WHITE,
+            );
+            for achievement in list.achievements.iter() {
+                // the locked ones are dimmed
+                let (name_color, description_color) = if unlocked.contains(&achievement.id) {
+                    (Color::ORANGE, Color::WHITE)
+                } else {
+                    (Color::rgb(0.4, 0.4, 0.4), Color::rgb(0.3, 0.3, 0.3))
+                };
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.0)),
+                            flex_direction: FlexDirection::ColumnReverse,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        material: button_materials.layout.clone(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        text(parent, achievement.name(), 24.0, name_color);
+                        text(parent, achievement.description(), 16.0, description_color);
+                    });
+            }
+        })
+        .push_children(&[menu])
+        .insert(AchievementsScreen);
+}
+
+fn screen_actions(
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    screen_query: Query<&Children, With<AchievementsScreen>>,
+    button_query: Query<&AchievementsButton>,
+) {
+    // the menu is a child of the screen
+    let back = menu_input.back().map_or(false, |menu| {
+        screen_query.iter().any(|children| children.contains(&menu))
+    });
+    let confirmed = menu_input
+        .confirmed()
+        .and_then(|button| button_query.get(button).ok());
+
+    if back || matches!(confirmed, Some(AchievementsButton::Back)) {
+        state
+            .set(GameState::Menu)
+            .expect("state: achievements -> menu");
+    }
+}
+
+fn cleanup(mut commands: Commands, screen_query: Query<(Entity, &AchievementsScreen)>) {
+    for (entity, _screen) in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
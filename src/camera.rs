@@ -0,0 +1,113 @@
+use crate::game::config::CONFIG;
+use bevy::{
+    prelude::*,
+    render::{camera::OrthographicProjection, pass::ClearColor},
+    window::{WindowCreated, WindowResized},
+};
+
+/// Scales the play area to fit the window, the space left around it is letterboxed
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<LetterboxMaterials>()
+            .add_startup_system(load_cameras.system())
+            .add_startup_system(setup_letterbox.system())
+            .add_system(fit_play_area.system())
+            .add_system(match_clear_color.system());
+    }
+}
+
+/// The 2d camera looking at the play area
+struct PlayAreaCamera;
+
+struct LetterboxMaterials {
+    bar: Handle<ColorMaterial>,
+}
+
+impl FromWorld for LetterboxMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let color = world
+            .get_resource::<ClearColor>()
+            .map_or(Color::BLACK, |clear_color| clear_color.0);
+        let mut materials = world
+            .get_resource_mut::<Assets<ColorMaterial>>()
+            .expect("world.assets#colorMaterial");
+        LetterboxMaterials {
+            bar: materials.add(color.into()),
+        }
+    }
+}
+
+/// Size in world units of what must always be visible: the play area and its walls
+pub fn logical_size() -> Vec2 {
+    Vec2::new(
+        CONFIG.play_area.width + 2.0 * CONFIG.wall_thickness,
+        CONFIG.play_area.height + 2.0 * CONFIG.wall_thickness,
+    )
+}
+
+fn load_cameras(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(PlayAreaCamera);
+    commands.spawn_bundle(UiCameraBundle::default());
+}
+
+/// Bars around the logical area hiding whatever is outside of it, whatever the window's aspect ratio
+fn setup_letterbox(mut commands: Commands, materials: Res<LetterboxMaterials>) {
+    let size = logical_size();
+    // large enough to cover any window once scaled
+    let bar = 10.0 * size.max_element();
+    let bars = vec![
+        (Vec2::new(-(size.x + bar) / 2.0, 0.0), Vec2::new(bar, bar)),
+        (Vec2::new((size.x + bar) / 2.0, 0.0), Vec2::new(bar, bar)),
+        (Vec2::new(0.0, (size.y + bar) / 2.0), Vec2::new(size.x, bar)),
+        (
+            Vec2::new(0.0, -(size.y + bar) / 2.0),
+            Vec2::new(size.x, bar),
+        ),
+    ];
+    for (position, bar_size) in bars {
+        commands.spawn_bundle(SpriteBundle {
+            material: materials.bar.clone(),
+            sprite: Sprite::new(bar_size),
+            transform: Transform::from_translation(position.extend(100.0)),
+            ..Default::default()
+        });
+    }
+}
+
+/// The bars blend with the background, which each theme sets when loaded
+fn match_clear_color(
+    clear_color: Res<ClearColor>,
+    letterbox: Res<LetterboxMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !clear_color.is_changed() {
+        return;
+    }
+    if let Some(bar) = materials.get_mut(&letterbox.bar) {
+        bar.color = clear_color.0;
+    }
+}
+
+/// Keeps the whole logical area in view, the projection is rebuilt by bevy on the same resize events
+fn fit_play_area(
+    windows: Res<Windows>,
+    mut created: EventReader<WindowCreated>,
+    mut resized: EventReader<WindowResized>,
+    mut camera_query: Query<&mut OrthographicProjection, With<PlayAreaCamera>>,
+) {
+    let changed = created.iter().count() + resized.iter().count() > 0;
+    let window = match windows.get_primary() {
+        Some(window) if changed => window,
+        _ => return,
+    };
+
+    let size = logical_size();
+    let scale = (size.x / window.width()).max(size.y / window.height());
+    for mut projection in camera_query.iter_mut() {
+        projection.scale = scale;
+    }
+}
@@ -0,0 +1,124 @@
+use super::{
+    balls::{Ball, BallId},
+    events::{BallLost, BrickDestroyed, PaddleHit},
+    run::Run,
+};
+use crate::{
+    achievements::{AchievementList, AchievementUnlocked, Condition, UnlockedAchievements},
+    GameState,
+};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Checks the conditions of the achievements as the game is played, unlocking the ones met
+pub struct GameAchievementsPlugin;
+
+impl Plugin for GameAchievementsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Tracker>();
+
+        app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(start_level.system()));
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("run")
+                .with_system(track.system().label("achievements"))
+                .with_system(check_achievements.system().after("achievements")),
+        );
+
+        app.add_system_set(
+            SystemSet::on_enter(GameState::LevelComplete).with_system(finish_level.system()),
+        );
+    }
+}
+
+/// What the achievements need to know about the level being played, on top of the run stats
+#[derive(Default)]
+struct Tracker {
+    lost_in_level: u32,
+    /// set once the level is cleared without losing a ball
+    flawless_level: bool,
+    /// bricks destroyed by each ball since it last bounced on the paddle
+    chains: HashMap<BallId, u32>,
+    longest_chain: u32,
+    most_balls: usize,
+}
+
+fn start_level(mut tracker: ResMut<Tracker>) {
+    *tracker = Tracker::default();
+}
+
+fn track(
+    mut tracker: ResMut<Tracker>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut ball_lost_events: EventReader<BallLost>,
+    balls_query: Query<&Ball>,
+) {
+    for hit in paddle_hit_events.iter() {
+        tracker.chains.remove(&hit.ball);
+    }
+    // the ones destroyed by lasers don't chain
+    for ball in destroyed_events
+        .iter()
+        .filter_map(|destroyed| destroyed.ball)
+    {
+        let chain = tracker.chains.entry(ball).or_default();
+        *chain += 1;
+        let chain = *chain;
+        tracker.longest_chain = tracker.longest_chain.max(chain);
+    }
+    for lost in ball_lost_events.iter() {
+        tracker.lost_in_level += 1;
+        tracker.chains.remove(&lost.ball);
+    }
+
+    tracker.most_balls = tracker.most_balls.max(balls_query.iter().count());
+}
+
+fn check_achievements(
+    list: Res<AchievementList>,
+    run: Res<Run>,
+    tracker: Res<Tracker>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    unlock_met(&list, &run, &tracker, &mut unlocked, &mut unlocked_events);
+}
+
+fn finish_level(
+    list: Res<AchievementList>,
+    run: Res<Run>,
+    mut tracker: ResMut<Tracker>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    tracker.flawless_level = tracker.lost_in_level == 0;
+    unlock_met(&list, &run, &tracker, &mut unlocked, &mut unlocked_events);
+}
+
+fn unlock_met(
+    list: &AchievementList,
+    run: &Run,
+    tracker: &Tracker,
+    unlocked: &mut UnlockedAchievements,
+    unlocked_events: &mut EventWriter<AchievementUnlocked>,
+) {
+    let stats = &run.stats;
+    for achievement in list.achievements.iter() {
+        let met = match achievement.condition {
+            Condition::LevelsCleared(levels) => stats.levels_cleared >= levels,
+            Condition::FlawlessLevel => tracker.flawless_level,
+            Condition::BallSpeed(speed) => stats.max_ball_speed >= speed,
+            Condition::BallsInPlay(balls) => tracker.most_balls >= balls,
+            Condition::Chain(bricks) => tracker.longest_chain >= bricks,
+            Condition::Rally(hits) => stats.longest_rally >= hits,
+            Condition::Score(score) => stats.score >= score,
+        };
+        if met && unlocked.unlock(&achievement.id) {
+            unlocked_events.send(AchievementUnlocked {
+                achievement: achievement.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use super::{
+    balls::Ball,
+    config::CONFIG,
+    paddle::{Attached, Paddle},
+    Disposable,
+};
+use crate::{settings::Settings, GameState};
+use bevy::prelude::*;
+
+/// Help for less experienced players, turned on in the options:
+/// the landing point of the ball, the paddle auto-aim and the safety net
+pub struct AssistsPlugin;
+
+impl Plugin for AssistsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<AssistMaterials>()
+            .init_resource::<Landing>()
+            .init_resource::<SafetyNet>();
+
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game).with_system(setup_indicators.system()),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .label("landing")
+                .with_system(predict_landing.system()),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("landing")
+                .with_system(update_indicators.system()),
+        );
+    }
+}
+
+/// Where the ball coming down will reach the paddle, bouncing on the side walls on the way
+#[derive(Default)]
+pub struct Landing {
+    pub x: Option<f32>,
+}
+
+/// Bounces the ball back up instead of losing it, once per life.
+/// There are no extra lives, so a life is a whole run and `start_run` arms it.
+#[derive(Default)]
+pub struct SafetyNet {
+    pub armed: bool,
+}
+
+/// thickness of the safety net, drawn at the bottom of the play area
+const NET_HEIGHT: f32 = 4.0;
+const INDICATOR_SIZE: f32 = 10.0;
+
+struct LandingIndicator;
+
+struct NetIndicator;
+
+struct AssistMaterials {
+    landing: Handle<ColorMaterial>,
+    net: Handle<ColorMaterial>,
+}
+
+impl FromWorld for AssistMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world
+            .get_resource_mut::<Assets<ColorMaterial>>()
+            .expect("world.assets#colorMaterial");
+        AssistMaterials {
+            landing: materials.add(Color::rgba(1.0, 1.0, 0.0, 0.7).into()),
+            net: materials.add(Color::rgba(0.3, 0.8, 1.0, 0.6).into()),
+        }
+    }
+}
+
+fn setup_indicators(mut commands: Commands, materials: Res<AssistMaterials>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            material: materials.landing.clone(),
+            sprite: Sprite::new(Vec2::new(INDICATOR_SIZE, INDICATOR_SIZE)),
+            visible: Visible {
+                is_visible: false,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .insert(LandingIndicator)
+        .insert(Disposable);
+
+    let bottom = -CONFIG.play_area.height / 2.0;
+    commands
+        .spawn_bundle(SpriteBundle {
+            material: materials.net.clone(),
+            sprite: Sprite::new(Vec2::new(CONFIG.play_area.width, NET_HEIGHT)),
+            transform: Transform::from_xyz(0.0, bottom + NET_HEIGHT / 2.0, 1.0),
+            visible: Visible {
+                is_visible: false,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .insert(NetIndicator)
+        .insert(Disposable);
+}
+
+/// The x where a ball at `position` going at `velocity` reaches `y`, if it's coming down to it
+fn landing_x(position: Vec3, velocity: Vec3, y: f32, radius: f32) -> Option<f32> {
+    if velocity.y >= 0.0 || position.y < y {
+        return None;
+    }
+
+    let time = (position.y - y) / -velocity.y;
+    let x = position.x + velocity.x * time;
+
+    // unfolds the bounces on the side walls
+    let limit = CONFIG.play_area.width / 2.0 - radius;
+    let folded = (x + limit).rem_euclid(4.0 * limit);
+    let folded = if folded > 2.0 * limit {
+        4.0 * limit - folded
+    } else {
+        folded
+    };
+    Some(folded - limit)
+}
+
+/// Predicts the landing of the ball that will reach the paddle first
+fn predict_landing(
+    mut landing: ResMut<Landing>,
+    paddle_query: Query<(&Transform, &Sprite), With<Paddle>>,
+    balls_query: Query<(&Ball, &Transform, &Sprite), Without<Attached>>,
+) {
+    landing.x = paddle_query
+        .single()
+        .ok()
+        .and_then(|(paddle, paddle_sprite)| {
+            let paddle_top = paddle.translation.y + paddle_sprite.size.y / 2.0;
+            balls_query
+                .iter()
+                .filter_map(|(ball, transform, sprite)| {
+                    let radius = sprite.size.x / 2.0;
+                    let position = transform.translation;
+                    let x = landing_x(position, ball.velocity, paddle_top + radius, radius)?;
+                    let time = (position.y - paddle_top) / -ball.velocity.y;
+                    Some((time, x))
+                })
+                .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(_, x)| x)
+        });
+}
+
+type Indicators<'a> = (
+    &'a mut Transform,
+    &'a mut Visible,
+    Option<&'a LandingIndicator>,
+);
+
+type AssistIndicators = Or<(With<LandingIndicator>, With<NetIndicator>)>;
+
+fn update_indicators(
+    settings: Res<Settings>,
+    landing: Res<Landing>,
+    net: Res<SafetyNet>,
+    paddle_query: Query<(&Transform, &Sprite), With<Paddle>>,
+    mut indicator_query: Query<Indicators, (AssistIndicators, Without<Paddle>)>,
+) {
+    let paddle_top = paddle_query
+        .single()
+        .map(|(transform, sprite)| transform.translation.y + sprite.size.y / 2.0)
+        .unwrap_or_default();
+
+    for (mut transform, mut visible, landing_indicator) in indicator_query.iter_mut() {
+        if landing_indicator.is_some() {
+            visible.is_visible = settings.landing_indicator && landing.x.is_some();
+            if let Some(x) = landing.x {
+                transform.translation = Vec3::new(x, paddle_top + INDICATOR_SIZE, 2.0);
+            }
+        } else {
+            visible.is_visible = settings.safety_net && net.armed;
+        }
+    }
+}
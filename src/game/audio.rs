@@ -0,0 +1,246 @@
+use super::{
+    config::CONFIG,
+    events::{BrickDestroyed, BrickHit, PaddleHit, WallHit},
+};
+use crate::{loading::SoundAssets, GameState};
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel};
+use rand::Rng;
+
+/// Sound effects of the game events, mixed by the `SoundManager`
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<SoundManager>();
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .label("sounds")
+                .with_system(brick_sounds.system())
+                .with_system(bounce_sounds.system()),
+        );
+
+        app.add_system_to_stage(CoreStage::PostUpdate, play_sounds.system());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SoundEffect {
+    BrickHit,
+    BrickDestroyed,
+    Paddle,
+    Wall,
+    Laser,
+}
+
+impl SoundEffect {
+    /// higher priority sounds take the voice of lower ones when none is free
+    fn priority(self) -> u8 {
+        match self {
+            SoundEffect::BrickDestroyed => 3,
+            SoundEffect::Paddle | SoundEffect::Laser => 2,
+            SoundEffect::BrickHit => 1,
+            SoundEffect::Wall => 0,
+        }
+    }
+
+    /// voices this sound can use at the same time
+    fn max_voices(self) -> usize {
+        match self {
+            SoundEffect::BrickHit | SoundEffect::BrickDestroyed => 4,
+            SoundEffect::Paddle | SoundEffect::Wall | SoundEffect::Laser => 2,
+        }
+    }
+
+    fn volume(self) -> f32 {
+        match self {
+            SoundEffect::Wall => 0.6,
+            _ => 1.0,
+        }
+    }
+
+    /// about as long as the longest variation, the voice is busy until then
+    fn length(self) -> f32 {
+        match self {
+            SoundEffect::BrickDestroyed => 0.5,
+            _ => 0.25,
+        }
+    }
+}
+
+/// A channel playing a single sound at a time, so it can be given its own volume,
+/// pitch and panning without changing the other sounds
+struct Voice {
+    channel: AudioChannel,
+    playing: Option<(SoundEffect, Timer)>,
+}
+
+impl Voice {
+    fn is_playing(&self, effect: SoundEffect) -> bool {
+        matches!(self.playing, Some((playing, _)) if playing == effect)
+    }
+}
+
+struct SoundRequest {
+    effect: SoundEffect,
+    /// x of every trigger of this frame
+    positions: Vec<f32>,
+}
+
+/// Plays the sound effects on a limited number of voices.
+/// Triggers of the same sound within a frame are played once, panned at their average x.
+pub struct SoundManager {
+    voices: Vec<Voice>,
+    requests: Vec<SoundRequest>,
+}
+
+impl FromWorld for SoundManager {
+    fn from_world(_world: &mut World) -> Self {
+        SoundManager {
+            voices: (0..CONFIG.sound.voices)
+                .map(|index| Voice {
+                    channel: AudioChannel::new(format!("sfx-{}", index)),
+                    playing: None,
+                })
+                .collect(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl SoundManager {
+    /// Queues the sound for the end of the frame, `x` is where it happened in the play area
+    pub fn play(&mut self, effect: SoundEffect, x: f32) {
+        match self
+            .requests
+            .iter_mut()
+            .find(|request| request.effect == effect)
+        {
+            Some(request) => request.positions.push(x),
+            None => self.requests.push(SoundRequest {
+                effect,
+                positions: vec![x],
+            }),
+        }
+    }
+
+    /// A free voice, or the one playing the least important sound if it's less important than `effect`
+    fn pick_voice(&mut self, effect: SoundEffect) -> Option<&mut Voice> {
+        if self
+            .voices
+            .iter()
+            .filter(|voice| voice.is_playing(effect))
+            .count()
+            >= effect.max_voices()
+        {
+            return None;
+        }
+
+        let index = match self.voices.iter().position(|voice| voice.playing.is_none()) {
+            Some(index) => index,
+            None => {
+                let (index, priority) = self
+                    .voices
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, voice)| {
+                        voice
+                            .playing
+                            .as_ref()
+                            .map(|(playing, _)| (index, playing.priority()))
+                    })
+                    .min_by_key(|(_, priority)| *priority)?;
+                if priority >= effect.priority() {
+                    return None;
+                }
+                index
+            }
+        };
+        self.voices.get_mut(index)
+    }
+}
+
+fn brick_sounds(
+    mut sound_manager: ResMut<SoundManager>,
+    mut hit_events: EventReader<BrickHit>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+) {
+    for hit in hit_events.iter() {
+        sound_manager.play(SoundEffect::BrickHit, hit.position.x);
+    }
+    for destroyed in destroyed_events.iter() {
+        sound_manager.play(SoundEffect::BrickDestroyed, destroyed.position.x);
+    }
+}
+
+fn bounce_sounds(
+    mut sound_manager: ResMut<SoundManager>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut wall_hit_events: EventReader<WallHit>,
+) {
+    for hit in paddle_hit_events.iter() {
+        sound_manager.play(SoundEffect::Paddle, hit.position.x);
+    }
+    for hit in wall_hit_events.iter() {
+        sound_manager.play(SoundEffect::Wall, hit.position.x);
+    }
+}
+
+/// Plays the sounds queued during the frame, the most important first
+fn play_sounds(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Option<Res<SoundAssets>>,
+    mut sound_manager: ResMut<SoundManager>,
+) {
+    // loaded with the rest of the assets
+    let sounds = match sounds {
+        Some(sounds) => sounds,
+        None => return,
+    };
+
+    for voice in sound_manager.voices.iter_mut() {
+        if let Some((_, timer)) = &mut voice.playing {
+            if timer.tick(time.delta()).finished() {
+                voice.playing = None;
+            }
+        }
+    }
+
+    let mut requests = std::mem::take(&mut sound_manager.requests);
+    requests.sort_by_key(|request| std::cmp::Reverse(request.effect.priority()));
+
+    let mut rng = rand::thread_rng();
+    for request in requests {
+        let voice = match sound_manager.pick_voice(request.effect) {
+            Some(voice) => voice,
+            None => continue,
+        };
+
+        let x = request.positions.iter().sum::<f32>() / request.positions.len() as f32;
+        let side = (x / (CONFIG.play_area.width / 2.0)).clamp(-1.0, 1.0);
+        let sound = &CONFIG.sound;
+        let volume = request.effect.volume() * (1.0 - rng.gen_range(0.0..=sound.volume_variation));
+        let pitch = 1.0 + rng.gen_range(-sound.pitch_variation..=sound.pitch_variation);
+
+        audio.stop_channel(&voice.channel);
+        audio.set_volume_in_channel(volume, &voice.channel);
+        audio.set_playback_rate_in_channel(pitch, &voice.channel);
+        // kira pans from 0.0 on the left to 1.0 on the right
+        audio.set_panning_in_channel(0.5 + side * sound.stereo_width / 2.0, &voice.channel);
+
+        let source = match request.effect {
+            SoundEffect::BrickHit | SoundEffect::Wall => sounds.hit.pick(),
+            SoundEffect::BrickDestroyed => sounds.explosion.pick(),
+            SoundEffect::Paddle => sounds.paddle.pick(),
+            SoundEffect::Laser => sounds.laser.pick(),
+        };
+        audio.play_in_channel(source, &voice.channel);
+        voice.playing = Some((
+            request.effect,
+            Timer::from_seconds(request.effect.length(), false),
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use super::{
+    balls::Ball,
+    config::CONFIG,
+    events::{BallLaunched, BallLost, PowerUpDropped},
+    paddle::{Attached, Paddle},
+};
+use crate::{loading::SoundAssets, settings::Settings, GameState};
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel};
+
+/// Audio cues telling where the ball is, enabled in the options.
+/// A tone follows the nearest ball coming down: panned to the side of the paddle it is on,
+/// higher as it gets closer to the paddle.
+pub struct AudioCuesPlugin;
+
+impl Plugin for AudioCuesPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(CueChannels {
+            tone: AudioChannel::new("cue-tone".to_owned()),
+            cues: AudioChannel::new("cues".to_owned()),
+            tone_playing: false,
+        });
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .with_system(follow_ball.system())
+                .with_system(ball_cues.system()),
+        );
+        app.add_system_set(SystemSet::on_pause(GameState::Game).with_system(stop_tone.system()));
+        app.add_system_set(SystemSet::on_exit(GameState::Game).with_system(stop_tone.system()));
+    }
+}
+
+/// playback rate of the tone when the ball is at the top and on the paddle's baseline
+const TONE_PITCH: (f32, f32) = (0.8, 2.0);
+const TONE_VOLUME: f32 = 0.5;
+
+struct CueChannels {
+    tone: AudioChannel,
+    cues: AudioChannel,
+    tone_playing: bool,
+}
+
+fn follow_ball(
+    settings: Res<Settings>,
+    audio: Res<Audio>,
+    sounds: Res<SoundAssets>,
+    mut channels: ResMut<CueChannels>,
+    paddle_query: Query<&Transform, With<Paddle>>,
+    balls_query: Query<(&Ball, &Transform), Without<Attached>>,
+) {
+    if !settings.audio_cues {
+        if channels.tone_playing {
+            audio.stop_channel(&channels.tone);
+            channels.tone_playing = false;
+        }
+        return;
+    }
+
+    let paddle = match paddle_query.single() {
+        Ok(transform) => transform.translation,
+        Err(_) => return,
+    };
+    let nearest = balls_query
+        .iter()
+        .filter(|(ball, _)| ball.velocity.y < 0.0)
+        .map(|(_, transform)| transform.translation)
+        .filter(|position| position.y > paddle.y)
+        .min_by(|a, b| a.y.partial_cmp(&b.y).unwrap_or(std::cmp::Ordering::Equal));
+
+    if !channels.tone_playing {
+        audio.set_volume_in_channel(0.0, &channels.tone);
+        audio.play_looped_in_channel(sounds.cue_tone.pick(), &channels.tone);
+        channels.tone_playing = true;
+    }
+
+    match nearest {
+        Some(ball) => {
+            let side = ((ball.x - paddle.x) / (CONFIG.play_area.width / 2.0)).clamp(-1.0, 1.0);
+            let top = CONFIG.play_area.height / 2.0;
+            let closeness = 1.0 - ((ball.y - paddle.y) / (top - paddle.y)).clamp(0.0, 1.0);
+            let (low, high) = TONE_PITCH;
+            audio.set_volume_in_channel(TONE_VOLUME, &channels.tone);
+            audio.set_panning_in_channel(0.5 + side / 2.0, &channels.tone);
+            audio.set_playback_rate_in_channel(low + (high - low) * closeness, &channels.tone);
+        }
+        // silent while every ball goes up
+        None => audio.set_volume_in_channel(0.0, &channels.tone),
+    }
+}
+
+/// A cue of its own for each ball launched and lost and each power-up dropped,
+/// panned to where it happened
+fn ball_cues(
+    settings: Res<Settings>,
+    audio: Res<Audio>,
+    sounds: Res<SoundAssets>,
+    channels: Res<CueChannels>,
+    mut launched_events: EventReader<BallLaunched>,
+    mut lost_events: EventReader<BallLost>,
+    mut dropped_events: EventReader<PowerUpDropped>,
+) {
+    let launched = launched_events
+        .iter()
+        .map(|launched| (launched.position, sounds.cue_launch.pick()));
+    let lost = lost_events
+        .iter()
+        .map(|lost| (lost.position, sounds.cue_lost.pick()));
+    let dropped = dropped_events
+        .iter()
+        .map(|dropped| (dropped.position, sounds.cue_power_up.pick()));
+    for (position, cue) in launched.chain(lost).chain(dropped) {
+        if !settings.audio_cues {
+            continue;
+        }
+        let side = (position.x / (CONFIG.play_area.width / 2.0)).clamp(-1.0, 1.0);
+        audio.set_panning_in_channel(0.5 + side / 2.0, &channels.cues);
+        audio.play_in_channel(cue, &channels.cues);
+    }
+}
+
+fn stop_tone(audio: Res<Audio>, mut channels: ResMut<CueChannels>) {
+    audio.stop_channel(&channels.tone);
+    channels.tone_playing = false;
+}
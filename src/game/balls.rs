@@ -1,25 +1,110 @@
-use super::{config::CONFIG, Disposable};
-use crate::{loading::MaterialsAssets, GameState};
+use super::{
+    assists::SafetyNet,
+    config::{SpeedRule, Tuning, CONFIG},
+    events::BallLost,
+    paddle::Attached,
+    Disposable,
+};
+use crate::{loading::MaterialsAssets, settings::Settings, GameState};
 use bevy::{math::Vec3, prelude::*};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
+
+/// Identifies a ball during the whole session, unlike its entity it's never reused
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct BallId(pub u32);
+
+#[derive(Default)]
+pub struct BallIds {
+    next: u32,
+}
+
+impl BallIds {
+    pub fn next(&mut self) -> BallId {
+        self.next += 1;
+        BallId(self.next)
+    }
+}
 
 #[derive(Default)]
 pub struct Ball {
+    pub id: BallId,
+    /// the ball this one was split from
+    pub parent: Option<BallId>,
     pub velocity: Vec3,
     pub speed: f32,
+    /// number of bricks hit by this ball
+    pub hits: u32,
+    /// highest brick row reached by this ball (0 is the top row)
+    pub top_row: Option<i32>,
+    pub touched_top: bool,
+    /// bricks destroyed by this ball that still have to be handled by the multi-ball policy
+    pub splits: u32,
 }
 
 impl Ball {
-    pub fn with_default_speed(direction: Vec3) -> Self {
+    pub fn with_speed(direction: Vec3, speed: f32) -> Self {
         Ball {
-            velocity: direction.normalize() * CONFIG.ball_starting_speed,
-            speed: CONFIG.ball_starting_speed,
+            velocity: direction.normalize() * speed,
+            speed,
+            ..Default::default()
         }
     }
 
-    pub fn new(velocity: Vec3) -> Self {
-        Ball {
-            velocity: velocity,
-            speed: velocity.length(),
+    /// increase the speed keeping the current direction
+    pub fn accelerate(&mut self, increase: f32) {
+        self.speed = (self.speed + increase).min(CONFIG.ball_max_speed);
+        if self.velocity != Vec3::default() {
+            self.velocity = self.speed * self.velocity.normalize();
+        }
+    }
+
+    pub fn on_paddle_hit(&mut self) {
+        for rule in CONFIG.speed_rules {
+            if let SpeedRule::PaddleHit { increase } = rule {
+                self.accelerate(*increase);
+            }
+        }
+    }
+
+    pub fn on_brick_hit(&mut self) {
+        self.hits += 1;
+        for rule in CONFIG.speed_rules {
+            if let SpeedRule::Hits { count, increase } = rule {
+                if *count == self.hits {
+                    self.accelerate(*increase);
+                }
+            }
+        }
+    }
+
+    fn on_row_reached(&mut self, row: i32) {
+        let previous = self.top_row.unwrap_or(CONFIG.brick_rows);
+        if row >= previous {
+            return;
+        }
+        self.top_row = Some(row);
+        for rule in CONFIG.speed_rules {
+            if let SpeedRule::BrickRow {
+                row: rule_row,
+                increase,
+            } = rule
+            {
+                if row <= *rule_row && *rule_row < previous {
+                    self.accelerate(*increase);
+                }
+            }
+        }
+    }
+
+    fn on_top_wall(&mut self) {
+        if self.touched_top {
+            return;
+        }
+        self.touched_top = true;
+        for rule in CONFIG.speed_rules {
+            if let SpeedRule::TopWall { increase, .. } = rule {
+                self.accelerate(*increase);
+            }
         }
     }
 }
@@ -30,7 +115,7 @@ impl Ball {
         materials: &Res<MaterialsAssets>,
         position: Vec2,
         ball: Ball,
-    ) {
+    ) -> Entity {
         commands
             .spawn_bundle(SpriteBundle {
                 material: materials.ball.clone(),
@@ -39,13 +124,16 @@ impl Ball {
                 ..Default::default()
             })
             .insert(Disposable)
-            .insert(ball);
+            .insert(ball)
+            .id()
     }
 }
 
 pub struct BallPlugin;
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<BallIds>();
+
         app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup_board.system()));
 
         app.add_system_set(
@@ -53,33 +141,122 @@ impl Plugin for BallPlugin {
                 .label("movement")
                 .with_system(ball_movement.system()),
         );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("movement")
+                .with_system(speed_progression.system())
+                .with_system(split_balls.system()),
+        );
     }
 }
 
-fn setup_board(mut commands: Commands, materials: Res<MaterialsAssets>) {
-    println!("setup game");
+fn setup_board(
+    mut commands: Commands,
+    materials: Res<MaterialsAssets>,
+    tuning: Res<Tuning>,
+    mut ids: ResMut<BallIds>,
+) {
+    debug!("setup game");
     // ball
     let base_line = -(CONFIG.play_area.height / 2.0) + 50.0;
-    let starting_height = base_line + (CONFIG.paddle_starting_size.height / 2.0) + 7.0;
-    Ball::spawn(
+    let starting_height = base_line + (tuning.paddle_size.1 / 2.0) + 7.0;
+    let ball = Ball::spawn(
         &mut commands,
         &materials,
         Vec2::new(0.0, starting_height),
-        Ball::default(),
+        Ball {
+            id: ids.next(),
+            ..Default::default()
+        },
     );
+    commands.entity(ball).insert(Attached { offset: 0.0 });
 }
 
 fn ball_movement(
     timer: Res<Time>,
+    settings: Res<Settings>,
+    mut net: ResMut<SafetyNet>,
     mut commands: Commands,
-    mut balls_query: Query<(Entity, &Ball, &mut Transform)>,
+    mut balls_query: Query<(Entity, &mut Ball, &mut Transform)>,
+    mut ball_lost_events: EventWriter<BallLost>,
 ) {
     let limit = -CONFIG.play_area.height / 2.0;
-    for (entity, ball, mut transform) in balls_query.iter_mut() {
-        transform.translation += ball.velocity * timer.delta_seconds();
-        if transform.translation.y < limit {
-            println!("Despawn ball");
+    for (entity, mut ball, mut transform) in balls_query.iter_mut() {
+        transform.translation += ball.velocity * timer.delta_seconds() * settings.game_speed;
+        if transform.translation.y < limit && settings.safety_net && net.armed {
+            debug!(ball = ball.id.0, "ball saved by the safety net");
+            net.armed = false;
+            ball.velocity.y = ball.velocity.y.abs();
+            transform.translation.y = limit;
+        } else if transform.translation.y < limit {
+            debug!(ball = ball.id.0, "ball lost");
+            ball_lost_events.send(BallLost {
+                ball: ball.id,
+                position: transform.translation,
+            });
             commands.entity(entity).despawn();
         }
     }
 }
+
+fn speed_progression(mut balls_query: Query<(&mut Ball, &Transform, &Sprite)>) {
+    let top = CONFIG.play_area.height / 2.0;
+    for (mut ball, transform, sprite) in balls_query.iter_mut() {
+        let ball_top = transform.translation.y + sprite.size.y / 2.0;
+
+        let row = ((top - ball_top) / CONFIG.brick_size.height).floor() as i32;
+        if row < CONFIG.brick_rows {
+            ball.on_row_reached(row.max(0));
+        }
+
+        if ball_top >= top {
+            ball.on_top_wall();
+        }
+    }
+}
+
+/// Spawns new balls for the bricks destroyed, following the multi-ball policy
+fn split_balls(
+    mut commands: Commands,
+    materials: Res<MaterialsAssets>,
+    tuning: Res<Tuning>,
+    mut ids: ResMut<BallIds>,
+    mut balls_query: Query<(&mut Ball, &Transform)>,
+) {
+    let policy = &CONFIG.multi_ball;
+    let spread = Uniform::from(-policy.split_spread..=policy.split_spread);
+    let mut rng = rand::thread_rng();
+    let mut count = balls_query.iter_mut().count();
+    for (mut ball, transform) in balls_query.iter_mut() {
+        if ball.splits == 0 {
+            continue;
+        }
+
+        let splits = std::mem::take(&mut ball.splits);
+        for _ in 0..splits {
+            if !rng.gen_bool(policy.spawn_chance) {
+                continue;
+            }
+
+            for _ in 1..policy.split_into {
+                if count >= policy.max_balls {
+                    break;
+                }
+
+                let angle = spread.sample(&mut rng).to_radians();
+                let direction = Vec3::new(angle.sin(), -angle.cos(), 0.0);
+                let mut split = Ball::with_speed(direction, tuning.ball_starting_speed);
+                split.id = ids.next();
+                split.parent = Some(ball.id);
+                Ball::spawn(
+                    &mut commands,
+                    &materials,
+                    transform.translation.into(),
+                    split,
+                );
+                count += 1;
+            }
+        }
+    }
+}
@@ -1,60 +1,147 @@
-use super::{config::CONFIG, Ball, Disposable};
+use super::{
+    balls::BallId,
+    config::CONFIG,
+    events::{BrickDestroyed, BrickHit},
+    laser::Laser,
+    level::Level,
+    run::Run,
+    Ball, Disposable,
+};
 use crate::{
-    loading::{BrickAssets, MaterialsAssets, SoundAssets},
+    accessibility::FixedTextSize,
+    loading::{BrickAssets, FontAssets, LevelAssets},
+    settings::{BrickLabels, Settings},
     GameState,
 };
 use bevy::{
     prelude::*,
     sprite::collide_aabb::{collide, Collision},
 };
-use bevy_kira_audio::{Audio, AudioChannel};
-use rand::{distributions::Uniform, prelude::Distribution};
+use rand::{distributions::Uniform, prelude::Distribution, Rng};
 
-struct Brick {
+pub struct Brick {
     life: u32,
+    /// it took to destroy it at the start of the level
+    hits: u32,
 }
 
-pub struct BrickPlugin;
+impl Brick {
+    /// Takes a life from the brick, returns true when there was none left and it should be destroyed.
+    /// A destroyed brick isn't marked as changed, so it isn't labelled again while being despawned.
+    fn hit(brick: &mut Mut<Brick>, sprite: &mut TextureAtlasSprite) -> bool {
+        if brick.life > 0 {
+            brick.life -= 1;
+            sprite.index = brick.life;
+            false
+        } else {
+            true
+        }
+    }
 
-struct AudioChannels {
-    brick_channel: AudioChannel,
+    /// Sends the event matching the outcome of `hit`
+    fn send_hit(
+        &self,
+        destroyed: bool,
+        ball: Option<BallId>,
+        position: Vec3,
+        hit_events: &mut EventWriter<BrickHit>,
+        destroyed_events: &mut EventWriter<BrickDestroyed>,
+    ) {
+        if destroyed {
+            destroyed_events.send(BrickDestroyed {
+                ball,
+                position,
+                hits: self.hits,
+            });
+        } else {
+            hit_events.send(BrickHit { ball, position });
+        }
+    }
 }
 
+pub struct BrickPlugin;
+
 impl Plugin for BrickPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.insert_resource(AudioChannels {
-            brick_channel: AudioChannel::new("brick".to_owned()),
-        });
+        app.init_resource::<BrickLabelMaterials>();
 
         app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup_board.system()));
 
         app.add_system_set(
             SystemSet::on_update(GameState::Game)
                 .after("movement")
-                .with_system(brick_collision.system()),
+                .label("collision")
+                .with_system(brick_collision.system())
+                .with_system(laser_brick_collision.system()),
         );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .with_system(paint_bricks.system())
+                .with_system(label_bricks.system()),
+        );
+    }
+}
+
+/// Shape drawn over the bricks for `BrickLabels::Patterns`
+struct BrickLabelMaterials {
+    dot: Handle<ColorMaterial>,
+}
+
+impl FromWorld for BrickLabelMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world
+            .get_resource_mut::<Assets<ColorMaterial>>()
+            .expect("world.assets#colorMaterial");
+        BrickLabelMaterials {
+            dot: materials.add(Color::BLACK.into()),
+        }
     }
 }
 
+/// Child of a brick showing the hits it has left
+struct BrickLabel;
+
+const LABEL_DOT_SIZE: f32 = 4.0;
+const LABEL_FONT_SIZE: f32 = 16.0;
+
 fn setup_board(
     mut commands: Commands,
-    // materials: Res<MaterialsAssets>,
     brick_assets: Res<BrickAssets>,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Level>>,
+    run: Res<Run>,
 ) {
-    println!("setup game bricks");
+    debug!("setup game bricks");
+    let level = levels.get(level_assets.current()).expect("bricks: level");
+    let mut rng = run.level_rng(level_assets.index);
+    spawn_bricks(&mut commands, &brick_assets, level, &mut rng);
+}
 
-    // bricks
-    let between = Uniform::from(0..3u32);
-    let mut rng = rand::thread_rng();
-    let bricks_per_row = (CONFIG.play_area.width / CONFIG.brick_size.width) as i32;
+/// Spawns the bricks of the level, anything past the play area is left out
+pub fn spawn_bricks(
+    commands: &mut Commands,
+    brick_assets: &BrickAssets,
+    level: &Level,
+    rng: &mut impl Rng,
+) {
+    let between = Uniform::from(0..brick_assets.frames);
+    let bricks_per_row = (CONFIG.play_area.width / CONFIG.brick_size.width) as usize;
     let brick_width = CONFIG.brick_size.width;
     let brick_height = CONFIG.brick_size.height;
-    for row_index in 0..CONFIG.brick_rows {
+    for (row_index, row) in level.rows.iter().enumerate() {
         let ri = row_index as f32 + 0.5; // count half a block since bevy 0,0 is in the middle of the block
         let starting_height = CONFIG.play_area.height / 2.0 - ri * brick_height;
-        for column_index in 0..bricks_per_row {
+        for (column_index, cell) in row.chars().take(bricks_per_row).enumerate() {
+            let brick_life = match cell {
+                '?' => between.sample(rng),
+                _ => match cell.to_digit(10) {
+                    Some(life) => life.min(brick_assets.frames - 1),
+                    None => continue,
+                },
+            };
             let ci = column_index as f32 + 0.5; // count half a block since bevy 0,0 is in the middle of the block
-            let brick_life = between.sample(&mut rng);
             commands
                 .spawn_bundle(SpriteSheetBundle {
                     texture_atlas: brick_assets.textures.clone(),
@@ -68,12 +155,20 @@ fn setup_board(
                             starting_height,
                             1.0,
                         ),
-                        scale: Vec3::new(brick_width / 200.0, brick_height / 70.0, 0.0),
+                        // an atlas sprite has no size of its own, the frame is scaled to fit
+                        scale: Vec3::new(
+                            brick_width / brick_assets.cell_size.x,
+                            brick_height / brick_assets.cell_size.y,
+                            1.0,
+                        ),
                         ..Default::default()
                     },
                     ..Default::default()
                 })
-                .insert(Brick { life: brick_life })
+                .insert(Brick {
+                    life: brick_life,
+                    hits: brick_life + 1,
+                })
                 .insert(Disposable);
         }
     }
@@ -83,10 +178,8 @@ fn brick_collision(
     mut ball_query: Query<(&mut Ball, &Transform, &Sprite)>,
     mut brick_query: Query<(Entity, &mut Brick, &Transform, &mut TextureAtlasSprite)>,
     mut commands: Commands,
-    materials: Res<MaterialsAssets>,
-    sounds: Res<SoundAssets>,
-    audio: Res<Audio>,
-    channels: Res<AudioChannels>,
+    mut hit_events: EventWriter<BrickHit>,
+    mut destroyed_events: EventWriter<BrickDestroyed>,
 ) {
     for (mut ball, ball_transform, ball_sprite) in ball_query.iter_mut() {
         let brick_size = Vec2::new(CONFIG.brick_size.width, CONFIG.brick_size.height);
@@ -105,22 +198,160 @@ fn brick_collision(
                     Collision::Top => ball.velocity.y = ball.velocity.y.abs(),
                     Collision::Bottom => ball.velocity.y = -ball.velocity.y.abs(),
                 };
+                ball.on_brick_hit();
 
-                if brick.life > 0 {
-                    audio.play_in_channel(sounds.hit.clone(), &channels.brick_channel);
-                    brick.life -= 1;
-                    sprite.index = brick.life;
-                } else {
-                    audio.play_in_channel(sounds.explosion.clone(), &channels.brick_channel);
-                    Ball::spawn(
-                        &mut commands,
-                        &materials,
-                        ball_position.into(),
-                        Ball::with_default_speed(Vec3::new(0.0, -1.0, 0.0)),
-                    );
-                    commands.entity(entity).despawn();
+                let destroyed = Brick::hit(&mut brick, &mut sprite);
+                brick.send_hit(
+                    destroyed,
+                    Some(ball.id),
+                    brick_position,
+                    &mut hit_events,
+                    &mut destroyed_events,
+                );
+                if destroyed {
+                    ball.splits += 1;
+                    commands.entity(entity).despawn_recursive();
                 }
             }
         }
     }
 }
+
+fn laser_brick_collision(
+    mut commands: Commands,
+    laser_query: Query<(Entity, &Laser, &Transform, &Sprite)>,
+    mut brick_query: Query<(Entity, &mut Brick, &Transform, &mut TextureAtlasSprite)>,
+    mut hit_events: EventWriter<BrickHit>,
+    mut destroyed_events: EventWriter<BrickDestroyed>,
+) {
+    let brick_size = Vec2::new(CONFIG.brick_size.width, CONFIG.brick_size.height);
+    let mut destroyed = vec![];
+    for (laser_entity, _laser, laser_transform, laser_sprite) in laser_query.iter() {
+        // only the first brick hit is damaged
+        let hit = brick_query
+            .iter_mut()
+            .filter(|(entity, ..)| !destroyed.contains(entity))
+            .find(|(_, _, transform, _)| {
+                collide(
+                    laser_transform.translation,
+                    laser_sprite.size,
+                    transform.translation,
+                    brick_size,
+                )
+                .is_some()
+            });
+
+        if let Some((entity, mut brick, transform, mut sprite)) = hit {
+            commands.entity(laser_entity).despawn();
+            let brick_destroyed = Brick::hit(&mut brick, &mut sprite);
+            brick.send_hit(
+                brick_destroyed,
+                None,
+                transform.translation,
+                &mut hit_events,
+                &mut destroyed_events,
+            );
+            if brick_destroyed {
+                destroyed.push(entity);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Tints the bricks with the colorblind palette picked in the options
+fn paint_bricks(
+    settings: Res<Settings>,
+    mut brick_query: Query<(&Brick, &mut TextureAtlasSprite, ChangeTrackers<Brick>)>,
+) {
+    for (brick, mut sprite, tracker) in brick_query.iter_mut() {
+        if settings.is_changed() || tracker.is_changed() {
+            sprite.color = settings
+                .brick_palette
+                .color(brick.life)
+                .unwrap_or(Color::WHITE);
+        }
+    }
+}
+
+/// Shows the hits left on the bricks as a number or dots, when enabled in the options
+fn label_bricks(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    font_assets: Res<FontAssets>,
+    materials: Res<BrickLabelMaterials>,
+    brick_query: Query<(Entity, &Brick, &Transform, ChangeTrackers<Brick>)>,
+    children_query: Query<&Children>,
+    label_query: Query<&BrickLabel>,
+) {
+    for (entity, brick, transform, tracker) in brick_query.iter() {
+        if !settings.is_changed() && !tracker.is_changed() {
+            continue;
+        }
+
+        if let Ok(children) = children_query.get(entity) {
+            for child in children.iter() {
+                if label_query.get(*child).is_ok() {
+                    commands.entity(*child).despawn_recursive();
+                }
+            }
+        }
+
+        // the brick is scaled to fit its atlas frame, its labels are scaled back
+        let unscale = Vec3::new(1.0 / transform.scale.x, 1.0 / transform.scale.y, 1.0);
+        let hits_left = brick.life + 1;
+        match settings.brick_labels {
+            BrickLabels::Off => {}
+            BrickLabels::Numbers => {
+                let label = commands
+                    .spawn_bundle(Text2dBundle {
+                        text: Text::with_section(
+                            hits_left.to_string(),
+                            TextStyle {
+                                font: font_assets.text_font.clone(),
+                                font_size: LABEL_FONT_SIZE,
+                                color: Color::BLACK,
+                            },
+                            TextAlignment {
+                                horizontal: HorizontalAlign::Center,
+                                vertical: VerticalAlign::Center,
+                            },
+                        ),
+                        transform: Transform {
+                            translation: Vec3::Z,
+                            scale: unscale,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(BrickLabel)
+                    .insert(FixedTextSize)
+                    .id();
+                commands.entity(entity).push_children(&[label]);
+            }
+            BrickLabels::Patterns => {
+                let spacing = LABEL_DOT_SIZE * 2.0;
+                let left = -((hits_left - 1) as f32) * spacing / 2.0;
+                let dots: Vec<Entity> = (0..hits_left)
+                    .map(|index| {
+                        let x = left + index as f32 * spacing;
+                        commands
+                            .spawn_bundle(SpriteBundle {
+                                material: materials.dot.clone(),
+                                sprite: Sprite::new(Vec2::new(LABEL_DOT_SIZE, LABEL_DOT_SIZE)),
+                                transform: Transform {
+                                    translation: Vec3::new(x, 0.0, 1.0) * unscale,
+                                    scale: unscale,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            })
+                            .insert(BrickLabel)
+                            .id()
+                    })
+                    .collect();
+                commands.entity(entity).push_children(&dots);
+            }
+        }
+    }
+}
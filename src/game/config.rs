@@ -1,12 +1,104 @@
-use bevy::math::Size;
+use crate::ron_asset::RonAsset;
+use bevy::{math::Size, reflect::TypeUuid};
+use serde::{de, Deserialize, Deserializer};
 
 pub struct GameConfig {
     pub play_area: Size,
     pub ball_starting_speed: f32,
+    pub ball_max_speed: f32,
     pub wall_thickness: f32,
     pub paddle_starting_size: Size,
     pub brick_size: Size,
     pub brick_rows: i32,
+    pub speed_rules: &'static [SpeedRule],
+    pub paddle_starting_mode: PaddleMode,
+    pub laser_size: Size,
+    pub laser_speed: f32,
+    /// seconds between each pair of lasers
+    pub laser_cooldown: f32,
+    /// probability of a destroyed brick dropping a laser power-up
+    pub laser_drop_chance: f64,
+    /// probability of a destroyed brick dropping a catch power-up, making the paddle sticky
+    pub catch_drop_chance: f64,
+    pub power_up_size: Size,
+    /// falling speed of the power-ups
+    pub power_up_speed: f32,
+    /// seconds the paddle keeps the mode of the last power-up it caught
+    pub power_up_duration: f32,
+    pub multi_ball: MultiBall,
+    /// points for a brick that takes the hit and survives
+    pub brick_hit_score: u32,
+    pub brick_destroyed_score: u32,
+    pub sound: SoundConfig,
+}
+
+/// How the sound effects are mixed, see `audio::SoundManager`
+pub struct SoundConfig {
+    /// sound effects playing at the same time, across all sounds
+    pub voices: usize,
+    /// the playback rate is picked at random up to this far from 1.0
+    pub pitch_variation: f32,
+    /// the volume is lowered at random by up to this much
+    pub volume_variation: f32,
+    /// 1.0 pans a sound at the edge of the play area all the way to one side, 0.0 keeps it centered
+    pub stereo_width: f32,
+}
+
+/// What happens to a ball when it destroys a brick
+pub struct MultiBall {
+    /// no more balls are spawned once there are this many in play
+    pub max_balls: usize,
+    /// number of balls, counting the original, that come out of a split
+    pub split_into: u32,
+    /// probability of a destroyed brick splitting the ball
+    pub spawn_chance: f64,
+    /// new balls go downwards at a random angle, in degrees, up to this spread
+    pub split_spread: f32,
+}
+
+/// Rules that make the ball faster as the game goes on.
+/// Each rule is applied per ball, the ones triggered by a "first time" only once.
+pub enum SpeedRule {
+    /// every time the ball hits the paddle
+    PaddleHit { increase: f32 },
+    /// once the ball has hit `count` bricks
+    Hits { count: u32, increase: f32 },
+    /// first time the ball reaches the brick `row` (0 is the top row)
+    BrickRow { row: i32, increase: f32 },
+    /// first time the ball touches the top wall, the paddle width is multiplied by `paddle_shrink`
+    TopWall { increase: f32, paddle_shrink: f32 },
+}
+
+/// How the ball bounces off the paddle.
+/// Angles are in degrees measured from the vertical, negative to the left.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub enum BounceModel {
+    /// the paddle is split in equal zones, left to right, each with a fixed angle
+    Classic {
+        #[serde(deserialize_with = "non_empty")]
+        zones: Vec<f32>,
+    },
+    /// the angle follows the hit offset from the paddle center
+    Continuous { min_angle: f32, max_angle: f32 },
+    /// the ball reflects and picks up `factor` of the paddle's horizontal velocity
+    English { factor: f32, max_angle: f32 },
+}
+
+fn non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    let zones = Vec::<f32>::deserialize(deserializer)?;
+    if zones.is_empty() {
+        return Err(de::Error::invalid_length(0, &"at least one zone"));
+    }
+    Ok(zones)
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PaddleMode {
+    Normal,
+    /// balls hitting the paddle stay attached until launched, after catching a catch power-up
+    Sticky,
+    /// the launch action fires lasers, once no ball is attached, after catching a laser power-up
+    Laser,
 }
 
 pub const CONFIG: GameConfig = GameConfig {
@@ -15,6 +107,7 @@ pub const CONFIG: GameConfig = GameConfig {
         height: 800.0,
     },
     ball_starting_speed: 300.0,
+    ball_max_speed: 1600.0,
     wall_thickness: 14.0,
     paddle_starting_size: Size {
         width: 140.0,
@@ -25,4 +118,146 @@ pub const CONFIG: GameConfig = GameConfig {
         height: 20.0,
     },
     brick_rows: 10,
+    speed_rules: &[
+        SpeedRule::PaddleHit { increase: 20.0 },
+        SpeedRule::Hits {
+            count: 4,
+            increase: 40.0,
+        },
+        SpeedRule::Hits {
+            count: 12,
+            increase: 60.0,
+        },
+        SpeedRule::BrickRow {
+            row: 4,
+            increase: 60.0,
+        },
+        SpeedRule::BrickRow {
+            row: 1,
+            increase: 80.0,
+        },
+        SpeedRule::TopWall {
+            increase: 0.0,
+            paddle_shrink: 0.5,
+        },
+    ],
+    paddle_starting_mode: PaddleMode::Normal,
+    laser_size: Size {
+        width: 4.0,
+        height: 14.0,
+    },
+    laser_speed: 900.0,
+    laser_cooldown: 0.4,
+    laser_drop_chance: 0.08,
+    catch_drop_chance: 0.06,
+    power_up_size: Size {
+        width: 30.0,
+        height: 12.0,
+    },
+    power_up_speed: 200.0,
+    power_up_duration: 10.0,
+    multi_ball: MultiBall {
+        max_balls: 8,
+        split_into: 2,
+        spawn_chance: 0.5,
+        split_spread: 45.0,
+    },
+    brick_hit_score: 10,
+    brick_destroyed_score: 50,
+    sound: SoundConfig {
+        voices: 12,
+        pitch_variation: 0.08,
+        volume_variation: 0.2,
+        stereo_width: 0.7,
+    },
 };
+
+/// Values the designers tune in `assets/game.config.ron`, hot-reloaded with the `dev` feature
+#[derive(Deserialize, TypeUuid, Clone, PartialEq, Debug)]
+#[uuid = "0b7c3f7e-5d2a-4b8e-8f61-3a9d4e2c1f05"]
+pub struct Tuning {
+    #[serde(deserialize_with = "positive")]
+    pub ball_starting_speed: f32,
+    #[serde(deserialize_with = "positive_size")]
+    pub paddle_size: (f32, f32),
+    #[serde(deserialize_with = "positive")]
+    pub wall_thickness: f32,
+    pub paddle_bounce: BounceModel,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            ball_starting_speed: CONFIG.ball_starting_speed,
+            paddle_size: (
+                CONFIG.paddle_starting_size.width,
+                CONFIG.paddle_starting_size.height,
+            ),
+            wall_thickness: CONFIG.wall_thickness,
+            paddle_bounce: BounceModel::Continuous {
+                min_angle: 5.0,
+                max_angle: 60.0,
+            },
+        }
+    }
+}
+
+/// A reloaded tuning is applied relative to the previous one, see `hot_reload::apply_tuning`
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(de::Error::invalid_value(
+            de::Unexpected::Float(value.into()),
+            &"a positive number",
+        ));
+    }
+    Ok(value)
+}
+
+fn positive_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(f32, f32), D::Error> {
+    let (width, height) = <(f32, f32)>::deserialize(deserializer)?;
+    for value in [width, height].iter() {
+        if !value.is_finite() || *value <= 0.0 {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Float((*value).into()),
+                &"a positive size",
+            ));
+        }
+    }
+    Ok((width, height))
+}
+
+impl RonAsset for Tuning {
+    const EXTENSIONS: &'static [&'static str] = &["config.ron"];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_bounce_needs_zones() {
+        let model = ron::de::from_str::<BounceModel>("Classic(zones: [-45.0, 45.0])");
+        assert_eq!(
+            model.unwrap(),
+            BounceModel::Classic {
+                zones: vec![-45.0, 45.0]
+            }
+        );
+        assert!(ron::de::from_str::<BounceModel>("Classic(zones: [])").is_err());
+    }
+
+    #[test]
+    fn tuning_needs_positive_sizes() {
+        let tuning = |ball_speed, paddle_width| {
+            ron::de::from_str::<Tuning>(&format!(
+                "(ball_starting_speed: {:?}, paddle_size: ({:?}, 20.0), wall_thickness: 14.0, \
+                 paddle_bounce: Continuous(min_angle: 5.0, max_angle: 60.0))",
+                ball_speed, paddle_width
+            ))
+        };
+        assert!(tuning(300.0, 140.0).is_ok());
+        assert!(tuning(0.0, 140.0).is_err());
+        assert!(tuning(300.0, -1.0).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use super::{balls::Ball, bricks::Brick, config::CONFIG, paddle::Paddle, walls::Wall};
+use crate::{loading::FontAssets, GameState};
+use bevy::{
+    diagnostic::{Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+/// Toggled with F3, shows stats and the collision boxes
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .init_resource::<DebugOverlay>()
+            .init_resource::<DebugMaterials>();
+
+        app.add_system(toggle_overlay.system());
+        app.add_system(update_stats.system());
+        app.add_system(update_aabbs.system());
+    }
+}
+
+#[derive(Default)]
+struct DebugOverlay {
+    enabled: bool,
+}
+
+struct DebugText;
+
+struct DebugAabb;
+
+struct DebugMaterials {
+    aabb: Handle<ColorMaterial>,
+}
+
+impl FromWorld for DebugMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world
+            .get_resource_mut::<Assets<ColorMaterial>>()
+            .expect("world.assets#colorMaterial");
+        DebugMaterials {
+            aabb: materials.add(Color::rgba(0.0, 1.0, 0.0, 0.3).into()),
+        }
+    }
+}
+
+fn toggle_overlay(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    font_assets: Option<Res<FontAssets>>,
+    text_query: Query<(Entity, &DebugText)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    overlay.enabled = !overlay.enabled;
+    for (entity, _text) in text_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if let (true, Some(font_assets)) = (overlay.enabled, font_assets) {
+        commands
+            .spawn_bundle(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        top: Val::Px(5.0),
+                        left: Val::Px(5.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text {
+                    sections: vec![TextSection {
+                        value: String::new(),
+                        style: TextStyle {
+                            font: font_assets.text_font.clone(),
+                            font_size: 14.0,
+                            color: Color::GREEN,
+                        },
+                    }],
+                    alignment: Default::default(),
+                },
+                ..Default::default()
+            })
+            .insert(DebugText);
+    }
+}
+
+fn update_stats(
+    overlay: Res<DebugOverlay>,
+    diagnostics: Res<Diagnostics>,
+    state: Res<State<GameState>>,
+    balls_query: Query<&Ball>,
+    bricks_query: Query<&Brick>,
+    mut text_query: Query<&mut Text, With<DebugText>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.average())
+        .unwrap_or_default();
+    let entities = diagnostics
+        .get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|count| count.value())
+        .unwrap_or_default();
+    let stack: Vec<String> = state
+        .inactives()
+        .iter()
+        .chain(std::iter::once(state.current()))
+        .map(|game_state| format!("{:?}", game_state))
+        .collect();
+
+    let mut value = format!(
+        "FPS: {:.1}\nentities: {} balls: {} bricks: {}\nstate: {}\n",
+        fps,
+        entities,
+        balls_query.iter().count(),
+        bricks_query.iter().count(),
+        stack.join(" > "),
+    );
+    for ball in balls_query.iter() {
+        value.push_str(&format!(
+            "ball #{}: velocity ({:.0}, {:.0}) speed {:.0}\n",
+            ball.id.0, ball.velocity.x, ball.velocity.y, ball.speed
+        ));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+type Collider<'a> = (Entity, &'a Transform, &'a Sprite);
+type SpriteColliders = (Or<(With<Wall>, With<Paddle>)>, Without<DebugAabb>);
+type BrickColliders = (With<Brick>, Without<DebugAabb>);
+
+/// Keeps a box over each wall, brick and the paddle while the overlay is enabled
+fn update_aabbs(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    materials: Res<DebugMaterials>,
+    mut aabbs: Local<HashMap<Entity, Entity>>,
+    sprite_query: Query<Collider, SpriteColliders>,
+    brick_query: Query<(Entity, &Transform), BrickColliders>,
+    mut aabb_query: Query<(&mut Transform, &mut Sprite), With<DebugAabb>>,
+) {
+    if !overlay.enabled {
+        for (_target, aabb) in aabbs.drain() {
+            commands.entity(aabb).despawn();
+        }
+        return;
+    }
+
+    let brick_size = Vec2::new(CONFIG.brick_size.width, CONFIG.brick_size.height);
+    let colliders = sprite_query
+        .iter()
+        .map(|(entity, transform, sprite)| (entity, transform.translation, sprite.size))
+        .chain(
+            brick_query
+                .iter()
+                .map(|(entity, transform)| (entity, transform.translation, brick_size)),
+        );
+
+    let mut targets = HashSet::default();
+    for (target, position, size) in colliders {
+        targets.insert(target);
+        let translation = position.truncate().extend(5.0);
+        match aabbs.get(&target) {
+            Some(aabb) => {
+                if let Ok((mut transform, mut sprite)) = aabb_query.get_mut(*aabb) {
+                    transform.translation = translation;
+                    sprite.size = size;
+                }
+            }
+            None => {
+                let aabb = commands
+                    .spawn_bundle(SpriteBundle {
+                        material: materials.aabb.clone(),
+                        sprite: Sprite::new(size),
+                        transform: Transform::from_translation(translation),
+                        ..Default::default()
+                    })
+                    .insert(DebugAabb)
+                    .id();
+                aabbs.insert(target, aabb);
+            }
+        }
+    }
+
+    // the target is gone, e.g. a destroyed brick
+    aabbs.retain(|target, aabb| {
+        if targets.contains(target) {
+            true
+        } else {
+            commands.entity(*aabb).despawn();
+            false
+        }
+    });
+}
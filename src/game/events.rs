@@ -0,0 +1,60 @@
+//! What happens during the game, sent by the collision systems.
+//! Audio, scoring, stats and effects each react to them on their own.
+
+use super::balls::BallId;
+use bevy::prelude::*;
+
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<BrickHit>()
+            .add_event::<BrickDestroyed>()
+            .add_event::<BallLaunched>()
+            .add_event::<BallLost>()
+            .add_event::<PaddleHit>()
+            .add_event::<WallHit>()
+            .add_event::<PowerUpDropped>();
+    }
+}
+
+/// A brick took a hit and survived it
+pub struct BrickHit {
+    /// `None` when it was hit by a laser
+    pub ball: Option<BallId>,
+    pub position: Vec3,
+}
+
+pub struct BrickDestroyed {
+    /// `None` when it was destroyed by a laser
+    pub ball: Option<BallId>,
+    pub position: Vec3,
+    /// it took from the start of the level, the kind of brick it was
+    pub hits: u32,
+}
+
+/// A ball left the paddle it was resting on
+pub struct BallLaunched {
+    pub position: Vec3,
+}
+
+/// A ball fell below the paddle
+pub struct BallLost {
+    pub ball: BallId,
+    pub position: Vec3,
+}
+
+/// A ball bounced off the top of the paddle
+pub struct PaddleHit {
+    pub ball: BallId,
+    pub position: Vec3,
+}
+
+pub struct WallHit {
+    pub position: Vec3,
+}
+
+/// A destroyed brick dropped a power-up
+pub struct PowerUpDropped {
+    pub position: Vec3,
+}
@@ -1,86 +1,104 @@
 use bevy::prelude::*;
 
-use crate::{loading::FontAssets, GameState};
+use crate::{
+    loading::LevelAssets,
+    localization::{Locale, Localized},
+    menu::{ButtonMaterials, MenuInput},
+    overlay::OverlayBuilder,
+    GameState,
+};
 
-use super::{balls::Ball, Disposable};
+use super::{
+    balls::Ball,
+    run::{start_run, Run},
+    Disposable,
+};
 
 pub struct GameOverPlugin;
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.init_resource::<GameOverMaterials>();
-
         app.add_system_set(SystemSet::on_update(GameState::Game).with_system(game_over.system()));
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::GameOver)
+                .after("menu_navigation")
+                .with_system(game_over_actions.system()),
+        );
     }
 }
 
-struct GameOverMaterials {
-    background: Handle<ColorMaterial>,
-}
-
-impl FromWorld for GameOverMaterials {
-    fn from_world(world: &mut World) -> Self {
-        let mut materials = world
-            .get_resource_mut::<Assets<ColorMaterial>>()
-            .expect("world.assets#colorMaterial");
-        GameOverMaterials {
-            background: materials.add(Color::rgba(1.0, 1.0, 1.0, 0.4).into()),
-        }
-    }
+enum GameOverButton {
+    Retry,
+    MainMenu,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn game_over(
     mut commands: Commands,
     mut state: ResMut<State<GameState>>,
-    font_assets: Res<FontAssets>,
-    materials: Res<GameOverMaterials>,
+    locale: Res<Locale>,
+    level_assets: Res<LevelAssets>,
+    run: Res<Run>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    button_materials: Res<ButtonMaterials>,
     balls_query: Query<&Ball>,
 ) {
     if balls_query.iter().next().is_none() {
         // not more balls left... gameover!
-        commands
-            .spawn_bundle(NodeBundle {
-                style: Style {
-                    size: Size::new(Val::Auto, Val::Auto),
-                    position_type: PositionType::Absolute,
-                    position: Rect {
-                        top: Val::Percent(15.0),
-                        left: Val::Percent(10.0),
-                        right: Val::Percent(10.0),
-                        ..Default::default()
-                    },
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::FlexEnd,
-                    ..Default::default()
+        let stats = &run.stats;
+        let time_played = stats.time_played as u32;
+        OverlayBuilder::new("game_over.title")
+            .stat(
+                "stats.score",
+                if stats.assisted {
+                    Localized::from("stats.assisted").arg("score", stats.score.to_string())
+                } else {
+                    stats.score.to_string().into()
                 },
-                material: materials.background.clone(),
-                ..Default::default()
-            })
-            .with_children(|parent| {
-                parent.spawn_bundle(TextBundle {
-                    style: Style {
-                        align_self: AlignSelf::Center,
-                        ..Default::default()
-                    },
-                    text: Text {
-                        sections: vec![TextSection {
-                            value: "GAME OVER".to_string(),
-                            style: TextStyle {
-                                font: font_assets.text_font.clone(),
-                                font_size: 72.0,
-                                color: Color::ORANGE,
-                            },
-                        }],
-                        alignment: TextAlignment {
-                            horizontal: HorizontalAlign::Center,
-                            vertical: VerticalAlign::Center,
-                        },
-                    },
-                    ..Default::default()
-                });
-            })
+            )
+            .stat("stats.bricks_destroyed", stats.bricks_destroyed.to_string())
+            .stat(
+                "stats.max_ball_speed",
+                format!("{:.0}", stats.max_ball_speed),
+            )
+            .stat("stats.longest_rally", stats.longest_rally.to_string())
+            .stat(
+                "stats.time_played",
+                format!("{}:{:02}", time_played / 60, time_played % 60),
+            )
+            .stat("stats.level", (level_assets.index + 1).to_string())
+            .button(GameOverButton::Retry, "game_over.retry")
+            .button(GameOverButton::MainMenu, "common.main_menu")
+            .spawn(&mut commands, &locale, &mut materials, &button_materials)
             .insert(Disposable);
         state
             .push(GameState::GameOver)
             .expect("state: game -> gameover");
     }
 }
+
+fn game_over_actions(
+    mut commands: Commands,
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    mut level_assets: ResMut<LevelAssets>,
+    run: Res<Run>,
+    button_query: Query<&GameOverButton>,
+) {
+    if let Some(Ok(button)) = menu_input
+        .confirmed()
+        .map(|button| button_query.get(button))
+    {
+        match button {
+            GameOverButton::Retry => {
+                start_run(&mut commands, &mut level_assets, run.seed);
+                state
+                    .replace(GameState::Game)
+                    .expect("state: gameover -> game");
+            }
+            GameOverButton::MainMenu => state
+                .replace(GameState::Menu)
+                .expect("state: gameover -> menu"),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::{
+    balls::Ball,
+    bricks::{spawn_bricks, Brick},
+    config::Tuning,
+    level::Level,
+    paddle::Paddle,
+    run::Run,
+    walls::Wall,
+};
+use crate::{
+    loading::{BrickAssets, LevelAssets},
+    GameState,
+};
+use bevy::prelude::*;
+
+/// Picks up changes to the levels, the tuning and the textures while the game runs
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(watch_assets.system())
+            .add_system(reload_tuning.system());
+
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game).with_system(record_tuning.system()),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(apply_tuning.system())
+                .with_system(reload_level.system()),
+        );
+    }
+}
+
+/// The tuning the entities on the board were set up with
+struct AppliedTuning(Tuning);
+
+fn watch_assets(asset_server: Res<AssetServer>) {
+    asset_server
+        .watch_for_changes()
+        .expect("hot reload: watch assets");
+    info!("watching assets for changes");
+}
+
+fn reload_tuning(
+    mut events: EventReader<AssetEvent<Tuning>>,
+    tunings: Res<Assets<Tuning>>,
+    tuning: Option<ResMut<Tuning>>,
+) {
+    let mut tuning = match tuning {
+        Some(tuning) => tuning,
+        None => return,
+    };
+
+    for event in events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            if let Some(reloaded) = tunings.get(handle) {
+                info!(?reloaded, "tuning reloaded");
+                *tuning = reloaded.clone();
+            }
+        }
+    }
+}
+
+fn record_tuning(mut commands: Commands, tuning: Res<Tuning>) {
+    commands.insert_resource(AppliedTuning(tuning.clone()));
+}
+
+/// Brings the paddle, balls and walls already on the board in line with the reloaded tuning
+fn apply_tuning(
+    tuning: Res<Tuning>,
+    mut applied: ResMut<AppliedTuning>,
+    mut paddle_query: Query<&mut Sprite, With<Paddle>>,
+    mut ball_query: Query<&mut Ball>,
+    mut wall_query: Query<(&Wall, &mut Transform, &mut Sprite), Without<Paddle>>,
+) {
+    if *tuning == applied.0 {
+        return;
+    }
+
+    // relative, so a shrunk paddle stays shrunk
+    for mut sprite in paddle_query.iter_mut() {
+        sprite.size.x *= tuning.paddle_size.0 / applied.0.paddle_size.0;
+        sprite.size.y *= tuning.paddle_size.1 / applied.0.paddle_size.1;
+    }
+
+    let speed_ratio = tuning.ball_starting_speed / applied.0.ball_starting_speed;
+    for mut ball in ball_query.iter_mut() {
+        ball.speed *= speed_ratio;
+        ball.velocity *= speed_ratio;
+    }
+
+    for (wall, mut transform, mut sprite) in wall_query.iter_mut() {
+        let (translation, size) = wall.layout(tuning.wall_thickness);
+        transform.translation = translation;
+        sprite.size = size;
+    }
+
+    applied.0 = tuning.clone();
+}
+
+/// Lays out the bricks again when the current level changes on disk
+fn reload_level(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<Level>>,
+    levels: Res<Assets<Level>>,
+    level_assets: Res<LevelAssets>,
+    brick_assets: Res<BrickAssets>,
+    brick_query: Query<Entity, With<Brick>>,
+    run: Res<Run>,
+) {
+    for event in events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            if handle != level_assets.current() {
+                continue;
+            }
+            if let Some(level) = levels.get(handle) {
+                info!("level reloaded");
+                for entity in brick_query.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                let mut rng = run.level_rng(level_assets.index);
+                spawn_bricks(&mut commands, &brick_assets, level, &mut rng);
+            }
+        }
+    }
+}
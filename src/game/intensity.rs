@@ -0,0 +1,57 @@
+use super::{
+    balls::Ball,
+    bricks::Brick,
+    config::{Tuning, CONFIG},
+};
+use crate::{music::MusicIntensity, GameState};
+use bevy::prelude::*;
+
+/// Tells the music how heated the game is
+pub struct IntensityPlugin;
+
+impl Plugin for IntensityPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .with_system(update_intensity.system()),
+        );
+        app.add_system_set(
+            SystemSet::on_exit(GameState::Game).with_system(reset_intensity.system()),
+        );
+    }
+}
+
+/// the intensity rises as the bricks left go below this
+const FEW_BRICKS: usize = 10;
+
+/// The highest of the fastest ball speed, the number of balls and how few bricks are left
+fn update_intensity(
+    mut intensity: ResMut<MusicIntensity>,
+    tuning: Res<Tuning>,
+    balls_query: Query<&Ball>,
+    bricks_query: Query<&Brick>,
+) {
+    let speed = balls_query
+        .iter()
+        .map(|ball| ball.speed)
+        .fold(tuning.ball_starting_speed, f32::max);
+    let speed =
+        (speed - tuning.ball_starting_speed) / (CONFIG.ball_max_speed - tuning.ball_starting_speed);
+
+    let max_balls = CONFIG.multi_ball.max_balls.max(2);
+    let balls = balls_query.iter().count().saturating_sub(1) as f32 / (max_balls - 1) as f32;
+
+    let bricks = bricks_query.iter().count();
+    let few_bricks = if bricks == 0 {
+        0.0
+    } else {
+        FEW_BRICKS.saturating_sub(bricks) as f32 / FEW_BRICKS as f32
+    };
+
+    intensity.0 = speed.max(balls).max(few_bricks).clamp(0.0, 1.0);
+}
+
+fn reset_intensity(mut intensity: ResMut<MusicIntensity>) {
+    intensity.0 = 0.0;
+}
@@ -0,0 +1,224 @@
+use super::{
+    audio::{SoundEffect, SoundManager},
+    config::{PaddleMode, CONFIG},
+    events::{BrickDestroyed, PowerUpDropped},
+    paddle::{Attached, Paddle},
+    Disposable,
+};
+use crate::{loading::MaterialsAssets, settings::Settings, GameState};
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+use rand::Rng;
+
+pub struct Laser;
+
+/// Dropped by a destroyed brick, puts the paddle in another mode for a while when caught
+#[derive(Clone, Copy)]
+pub enum PowerUp {
+    Laser,
+    /// the paddle turns sticky
+    Catch,
+}
+
+impl PowerUp {
+    fn mode(self) -> PaddleMode {
+        match self {
+            PowerUp::Laser => PaddleMode::Laser,
+            PowerUp::Catch => PaddleMode::Sticky,
+        }
+    }
+}
+
+struct LaserCooldown(Timer);
+
+/// Time left in the mode of the last power-up caught
+struct PowerUpMode(Timer);
+
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(LaserCooldown(Timer::from_seconds(
+            CONFIG.laser_cooldown,
+            false,
+        )))
+        .insert_resource(PowerUpMode(Timer::from_seconds(
+            CONFIG.power_up_duration,
+            false,
+        )));
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .with_system(fire_lasers.system())
+                .with_system(drop_power_ups.system())
+                .with_system(expire_power_ups.system()),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .label("movement")
+                .with_system(laser_movement.system())
+                .with_system(power_up_movement.system()),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("movement")
+                .with_system(catch_power_ups.system()),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fire_lasers(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut cooldown: ResMut<LaserCooldown>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    paddle_query: Query<(&Paddle, &Transform, &Sprite)>,
+    attached_query: Query<(), With<Attached>>,
+    materials: Res<MaterialsAssets>,
+    mut sound_manager: ResMut<SoundManager>,
+) {
+    cooldown.0.tick(time.delta().mul_f32(settings.game_speed));
+
+    if let Ok((paddle, transform, sprite)) = paddle_query.single() {
+        // the click launches the attached balls instead
+        if paddle.mode != PaddleMode::Laser
+            || attached_query.iter().next().is_some()
+            || !cooldown.0.finished()
+            || !mouse_button_input.just_pressed(MouseButton::Left)
+        {
+            return;
+        }
+
+        cooldown.0.reset();
+        sound_manager.play(SoundEffect::Laser, transform.translation.x);
+
+        // one laser from each edge of the paddle
+        let edge = (sprite.size.x - CONFIG.laser_size.width) / 2.0;
+        let y = transform.translation.y + (sprite.size.y + CONFIG.laser_size.height) / 2.0;
+        for x in [-edge, edge].iter() {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    material: materials.laser.clone(),
+                    sprite: Sprite::new(Vec2::new(
+                        CONFIG.laser_size.width,
+                        CONFIG.laser_size.height,
+                    )),
+                    transform: Transform::from_xyz(transform.translation.x + x, y, 1.0),
+                    ..Default::default()
+                })
+                .insert(Laser)
+                .insert(Disposable);
+        }
+    }
+}
+
+fn laser_movement(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut laser_query: Query<(&Laser, &mut Transform)>,
+) {
+    for (_laser, mut transform) in laser_query.iter_mut() {
+        transform.translation.y += CONFIG.laser_speed * time.delta_seconds() * settings.game_speed;
+    }
+}
+
+fn drop_power_ups(
+    mut commands: Commands,
+    materials: Res<MaterialsAssets>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+    mut dropped_events: EventWriter<PowerUpDropped>,
+) {
+    let mut rng = rand::thread_rng();
+    for destroyed in destroyed_events.iter() {
+        // at most one from a brick, the laser is tried first
+        let (power_up, material) = if rng.gen_bool(CONFIG.laser_drop_chance) {
+            (PowerUp::Laser, &materials.laser)
+        } else if rng.gen_bool(CONFIG.catch_drop_chance) {
+            (PowerUp::Catch, &materials.paddle)
+        } else {
+            continue;
+        };
+        commands
+            .spawn_bundle(SpriteBundle {
+                material: material.clone(),
+                sprite: Sprite::new(Vec2::new(
+                    CONFIG.power_up_size.width,
+                    CONFIG.power_up_size.height,
+                )),
+                transform: Transform::from_xyz(destroyed.position.x, destroyed.position.y, 1.0),
+                ..Default::default()
+            })
+            .insert(power_up)
+            .insert(Disposable);
+        dropped_events.send(PowerUpDropped {
+            position: destroyed.position,
+        });
+    }
+}
+
+/// Drops the power-ups, the ones the paddle missed are gone once out of the play area
+fn power_up_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut power_up_query: Query<(Entity, &mut Transform), With<PowerUp>>,
+) {
+    for (entity, mut transform) in power_up_query.iter_mut() {
+        transform.translation.y -=
+            CONFIG.power_up_speed * time.delta_seconds() * settings.game_speed;
+        if transform.translation.y < -CONFIG.play_area.height / 2.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn catch_power_ups(
+    mut commands: Commands,
+    mut power_up_mode: ResMut<PowerUpMode>,
+    mut paddle_query: Query<(&mut Paddle, &Transform, &Sprite)>,
+    power_up_query: Query<(Entity, &PowerUp, &Transform, &Sprite)>,
+) {
+    if let Ok((mut paddle, paddle_transform, paddle_sprite)) = paddle_query.single_mut() {
+        for (entity, power_up, transform, sprite) in power_up_query.iter() {
+            let caught = collide(
+                transform.translation,
+                sprite.size,
+                paddle_transform.translation,
+                paddle_sprite.size,
+            )
+            .is_some();
+            if caught {
+                commands.entity(entity).despawn();
+                // another one replaces the mode and restarts the time left
+                power_up_mode.0.reset();
+                paddle.mode = power_up.mode();
+                debug!(mode = ?paddle.mode, "power-up caught");
+            }
+        }
+    }
+}
+
+/// Puts the paddle back in its starting mode once the power-up runs out,
+/// the balls it caught stay attached until launched
+fn expire_power_ups(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut power_up_mode: ResMut<PowerUpMode>,
+    mut paddle_query: Query<&mut Paddle>,
+) {
+    if let Ok(mut paddle) = paddle_query.single_mut() {
+        if paddle.mode == CONFIG.paddle_starting_mode {
+            return;
+        }
+        power_up_mode
+            .0
+            .tick(time.delta().mul_f32(settings.game_speed));
+        if power_up_mode.0.finished() {
+            paddle.mode = CONFIG.paddle_starting_mode;
+            debug!("power-up over");
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use crate::ron_asset::RonAsset;
+use bevy::reflect::TypeUuid;
+use serde::Deserialize;
+
+/// Brick layout, read from the `.level.ron` files in `assets/levels`
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "9e4a0d1b-7c6f-4a3e-b2d8-5f1e6c7a8b90"]
+pub struct Level {
+    /// top row first, one character per brick:
+    /// `.` for no brick, a digit for the brick's life or `?` for a random life
+    pub rows: Vec<String>,
+    /// name of the theme's music track played instead of the usual game music, for boss levels
+    #[serde(default)]
+    pub music: Option<String>,
+}
+
+impl RonAsset for Level {
+    const EXTENSIONS: &'static [&'static str] = &["level.ron"];
+}
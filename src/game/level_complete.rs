@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::{
+    loading::LevelAssets,
+    localization::{Locale, Localized},
+    menu::{ButtonMaterials, MenuInput},
+    overlay::OverlayBuilder,
+    GameState,
+};
+
+use super::{bricks::Brick, run::Run, Ball, Disposable};
+
+pub struct LevelCompletePlugin;
+impl Plugin for LevelCompletePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game).with_system(level_complete.system()),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::LevelComplete)
+                .after("menu_navigation")
+                .with_system(level_complete_actions.system()),
+        );
+    }
+}
+
+enum LevelCompleteButton {
+    NextLevel,
+    MainMenu,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn level_complete(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    locale: Res<Locale>,
+    level_assets: Res<LevelAssets>,
+    mut run: ResMut<Run>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    button_materials: Res<ButtonMaterials>,
+    bricks_query: Query<&Brick>,
+    balls_query: Query<&Ball>,
+) {
+    // losing the last ball along with the last brick is still a game over
+    let ball_left = balls_query.iter().next().is_some();
+    if ball_left && bricks_query.iter().next().is_none() {
+        run.stats.levels_cleared += 1;
+        OverlayBuilder::new("level_complete.title")
+            .subtitle(
+                Localized::from("level_complete.level")
+                    .arg("level", (level_assets.index + 1).to_string()),
+            )
+            .button(LevelCompleteButton::NextLevel, "level_complete.next")
+            .button(LevelCompleteButton::MainMenu, "common.main_menu")
+            .spawn(&mut commands, &locale, &mut materials, &button_materials)
+            .insert(Disposable);
+        state
+            .push(GameState::LevelComplete)
+            .expect("state: game -> level complete");
+    }
+}
+
+fn level_complete_actions(
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    mut level_assets: ResMut<LevelAssets>,
+    button_query: Query<&LevelCompleteButton>,
+) {
+    if let Some(Ok(button)) = menu_input
+        .confirmed()
+        .map(|button| button_query.get(button))
+    {
+        match button {
+            LevelCompleteButton::NextLevel => {
+                // the board is set up again with the next level when the game is entered
+                level_assets.next();
+                state
+                    .replace(GameState::Game)
+                    .expect("state: level complete -> game");
+            }
+            LevelCompleteButton::MainMenu => state
+                .replace(GameState::Menu)
+                .expect("state: level complete -> menu"),
+        }
+    }
+}
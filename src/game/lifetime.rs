@@ -0,0 +1,70 @@
+use super::{
+    events::{BallLost, BrickDestroyed, PaddleHit},
+    run::Run,
+};
+use crate::{loading::LevelAssets, profiles::Profiles, GameState};
+use bevy::{app::AppExit, prelude::*};
+
+/// Adds what happens in the game to the lifetime stats of the profile playing
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(count_game.system()));
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("run")
+                .with_system(track_lifetime.system()),
+        );
+
+        // the game can be left for good from any of the overlays
+        app.add_system_set(
+            SystemSet::on_pause(GameState::Game).with_system(save_profiles.system()),
+        );
+        app.add_system_set(SystemSet::on_exit(GameState::Game).with_system(save_profiles.system()));
+        // or closed mid-game, after the exit is requested during the update
+        app.add_system_to_stage(CoreStage::Last, save_on_exit.system());
+    }
+}
+
+/// A game starts on the first level with none cleared, the game is entered again for each level
+fn count_game(level_assets: Res<LevelAssets>, run: Res<Run>, mut profiles: ResMut<Profiles>) {
+    if level_assets.index != 0 || run.stats.levels_cleared != 0 {
+        return;
+    }
+    if let Some(profile) = profiles.current_mut() {
+        profile.stats.games_played += 1;
+    }
+}
+
+fn track_lifetime(
+    time: Res<Time>,
+    run: Res<Run>,
+    mut profiles: ResMut<Profiles>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut ball_lost_events: EventReader<BallLost>,
+) {
+    let stats = match profiles.current_mut() {
+        Some(profile) => &mut profile.stats,
+        None => return,
+    };
+    stats.time_played += time.delta_seconds();
+    for destroyed in destroyed_events.iter() {
+        *stats.bricks_destroyed.entry(destroyed.hits).or_default() += 1;
+    }
+    stats.paddle_hits += paddle_hit_events.iter().count() as u32;
+    stats.balls_lost += ball_lost_events.iter().count() as u32;
+    stats.best_level = stats.best_level.max(run.stats.levels_cleared + 1);
+}
+
+fn save_profiles(profiles: Res<Profiles>) {
+    profiles.save();
+}
+
+fn save_on_exit(profiles: Res<Profiles>, mut exit_events: EventReader<AppExit>) {
+    if exit_events.iter().next().is_some() {
+        profiles.save();
+    }
+}
@@ -1,19 +1,37 @@
-use crate::{loading::SoundAssets, GameState};
+use crate::{ron_asset::RonAssetLoader, GameState};
 use balls::Ball;
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioChannel};
 
 use self::{
-    balls::BallPlugin, bricks::BrickPlugin, gameover::GameOverPlugin, paddle::PaddlePlugin,
-    pause::PausePlugin, walls::WallPlugin,
+    achievements::GameAchievementsPlugin, assists::AssistsPlugin, audio::GameAudioPlugin,
+    audio_cues::AudioCuesPlugin, balls::BallPlugin, bricks::BrickPlugin, config::Tuning,
+    debug::DebugPlugin, events::EventsPlugin, gameover::GameOverPlugin, intensity::IntensityPlugin,
+    laser::LaserPlugin, level::Level, level_complete::LevelCompletePlugin,
+    lifetime::LifetimePlugin, paddle::PaddlePlugin, particles::ParticlesPlugin, pause::PausePlugin,
+    run::RunPlugin, walls::WallPlugin,
 };
 
+mod achievements;
+mod assists;
+mod audio;
+mod audio_cues;
 mod balls;
 mod bricks;
 pub mod config;
+mod debug;
+mod events;
 mod gameover;
+#[cfg(feature = "dev")]
+mod hot_reload;
+mod intensity;
+mod laser;
+pub mod level;
+mod level_complete;
+mod lifetime;
 mod paddle;
+mod particles;
 mod pause;
+pub mod run;
 pub mod walls;
 
 pub struct Disposable;
@@ -22,7 +40,15 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<Tuning>()
+            .init_asset_loader::<RonAssetLoader<Tuning>>()
+            .add_asset::<Level>()
+            .init_asset_loader::<RonAssetLoader<Level>>();
+
         app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(hide_mouse.system()));
+        // the overlays on top of the game have buttons
+        app.add_system_set(SystemSet::on_pause(GameState::Game).with_system(show_mouse.system()));
+        app.add_system_set(SystemSet::on_resume(GameState::Game).with_system(hide_mouse.system()));
 
         app.add_system(handle_keyboard_esc.system());
 
@@ -30,12 +56,26 @@ impl Plugin for GamePlugin {
             SystemSet::on_exit(GameState::Game).with_system(cleanup.system()), // .with_system(stop_music.system()),
         );
 
+        app.add_plugin(EventsPlugin);
         app.add_plugin(WallPlugin);
         app.add_plugin(BrickPlugin);
         app.add_plugin(BallPlugin);
         app.add_plugin(PaddlePlugin);
+        app.add_plugin(LaserPlugin);
         app.add_plugin(PausePlugin);
         app.add_plugin(GameOverPlugin);
+        app.add_plugin(LevelCompletePlugin);
+        app.add_plugin(RunPlugin);
+        app.add_plugin(GameAudioPlugin);
+        app.add_plugin(ParticlesPlugin);
+        app.add_plugin(IntensityPlugin);
+        app.add_plugin(AudioCuesPlugin);
+        app.add_plugin(AssistsPlugin);
+        app.add_plugin(GameAchievementsPlugin);
+        app.add_plugin(LifetimePlugin);
+        app.add_plugin(DebugPlugin);
+        #[cfg(feature = "dev")]
+        app.add_plugin(hot_reload::HotReloadPlugin);
     }
 }
 
@@ -47,16 +87,23 @@ fn hide_mouse(mut windows: ResMut<Windows>, mut mouse_button_input: ResMut<Input
     }
 }
 
+fn show_mouse(mut windows: ResMut<Windows>) {
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_cursor_lock_mode(false);
+        window.set_cursor_visibility(true);
+    }
+}
+
 fn handle_keyboard_esc(
     mut keyboard_input: ResMut<Input<KeyCode>>,
     mut state: ResMut<State<GameState>>,
 ) {
     match state.current() {
-        GameState::Game | GameState::GameOver | GameState::Pause => {
-            if keyboard_input.just_pressed(KeyCode::Escape) {
-                keyboard_input.reset(KeyCode::Escape);
-                state.replace(GameState::Menu).expect("state: game -> menu");
-            }
+        GameState::GameOver | GameState::Pause | GameState::LevelComplete
+            if keyboard_input.just_pressed(KeyCode::Escape) =>
+        {
+            keyboard_input.reset(KeyCode::Escape);
+            state.replace(GameState::Menu).expect("state: game -> menu");
         }
         _ => {}
     }
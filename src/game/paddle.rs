@@ -1,22 +1,25 @@
-use super::{config::CONFIG, Ball, Disposable};
-use crate::{
-    loading::{MaterialsAssets, SoundAssets},
-    GameState,
+use super::{
+    assists::Landing,
+    config::{BounceModel, PaddleMode, SpeedRule, Tuning, CONFIG},
+    events::{BallLaunched, PaddleHit},
+    Ball, Disposable,
 };
+use crate::{loading::MaterialsAssets, settings::Settings, GameState};
 use bevy::{
     input::mouse::MouseMotion,
     math::Vec2,
     prelude::*,
     sprite::{collide_aabb::collide, collide_aabb::Collision, Sprite},
 };
-use bevy_kira_audio::Audio;
 
 pub struct PaddlePlugin;
 
 impl Plugin for PaddlePlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup_board.system()));
-        app.add_system_set(SystemSet::on_update(GameState::Game).with_system(start_ball.system()));
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game).with_system(release_balls.system()),
+        );
 
         app.add_system_set(
             SystemSet::on_update(GameState::Game)
@@ -27,51 +30,104 @@ impl Plugin for PaddlePlugin {
         app.add_system_set(
             SystemSet::on_update(GameState::Game)
                 .after("movement")
-                .with_system(ball_paddle_collision.system()),
+                .label("collision")
+                .with_system(ball_paddle_collision.system())
+                .with_system(shrink_paddle.system()),
         );
     }
 }
 
-pub struct Paddle;
+pub struct Paddle {
+    pub mode: PaddleMode,
+    pub shrunk: bool,
+    /// horizontal velocity, used by the english bounce model
+    pub velocity: f32,
+}
+
+impl Default for Paddle {
+    fn default() -> Self {
+        Paddle {
+            mode: CONFIG.paddle_starting_mode,
+            shrunk: false,
+            velocity: 0.0,
+        }
+    }
+}
+
+/// A ball resting on the paddle, waiting to be launched
+pub struct Attached {
+    /// horizontal distance from the paddle center
+    pub offset: f32,
+}
 
-fn setup_board(mut commands: Commands, materials: Res<MaterialsAssets>) {
+fn setup_board(mut commands: Commands, materials: Res<MaterialsAssets>, tuning: Res<Tuning>) {
     // paddle
     let base_line = -(CONFIG.play_area.height / 2.0) + 50.0;
     commands
         .spawn_bundle(SpriteBundle {
             material: materials.paddle.clone(),
-            sprite: Sprite::new(Vec2::new(
-                CONFIG.paddle_starting_size.width,
-                CONFIG.paddle_starting_size.height,
-            )),
+            sprite: Sprite::new(Vec2::new(tuning.paddle_size.0, tuning.paddle_size.1)),
             transform: Transform::from_translation(Vec3::new(0., base_line, 1.)),
             ..Default::default()
         })
-        .insert(Paddle)
+        .insert(Paddle::default())
         .insert(Disposable);
 }
 
+/// fraction of the distance to the landing point the auto-aim moves the paddle, per second
+const AUTO_AIM_STRENGTH: f32 = 4.0;
+/// the most the auto-aim moves the paddle in a frame
+const AUTO_AIM_SPEED: f32 = 8.0;
+
+type AttachedBall<'a> = (&'a Attached, &'a mut Transform, &'a Sprite);
+
 fn paddle_movement(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    landing: Res<Landing>,
     mut mouse_motion_events: EventReader<MouseMotion>,
-    mut query: Query<(&Paddle, &mut Transform)>,
+    mut query: Query<(&mut Paddle, &mut Transform, &Sprite)>,
+    mut attached_query: Query<AttachedBall, Without<Paddle>>,
 ) {
-    if let Ok((_paddle, mut transform)) = query.single_mut() {
+    if let Ok((mut paddle, mut transform, sprite)) = query.single_mut() {
         let delta: f32 = mouse_motion_events.iter().map(|e| e.delta.x).sum();
+        let previous_x = transform.translation.x;
         transform.translation.x += delta;
+        // the mouse moves the paddle as fast as ever, only what follows the game time slows down
+        let delta_seconds = time.delta_seconds() * settings.game_speed;
+        if let (true, Some(x)) = (settings.auto_aim, landing.x) {
+            let nudge = (x - transform.translation.x) * AUTO_AIM_STRENGTH * delta_seconds;
+            transform.translation.x += nudge.clamp(
+                -AUTO_AIM_SPEED * settings.game_speed,
+                AUTO_AIM_SPEED * settings.game_speed,
+            );
+        }
 
-        let limit = (CONFIG.play_area.width / 2.0) - (CONFIG.paddle_starting_size.width / 2.0);
+        let limit = (CONFIG.play_area.width / 2.0) - (sprite.size.x / 2.0);
         transform.translation.x = transform.translation.x.clamp(-limit, limit);
+
+        if delta_seconds > 0.0 {
+            paddle.velocity = (transform.translation.x - previous_x) / delta_seconds;
+        }
+
+        // attached balls follow the paddle
+        for (attached, mut ball_transform, ball_sprite) in attached_query.iter_mut() {
+            ball_transform.translation.x = transform.translation.x + attached.offset;
+            ball_transform.translation.y =
+                transform.translation.y + (sprite.size.y + ball_sprite.size.y) / 2.0;
+        }
     }
 }
 
 fn ball_paddle_collision(
-    mut ball_query: Query<(&mut Ball, &Transform, &Sprite)>,
+    mut commands: Commands,
+    tuning: Res<Tuning>,
+    mut ball_query: Query<(Entity, &mut Ball, &Transform, &Sprite), Without<Attached>>,
     paddle_query: Query<(&Paddle, &Transform, &Sprite)>,
-    audio: Res<Audio>,
-    sounds: Res<SoundAssets>,
+    mut paddle_hit_events: EventWriter<PaddleHit>,
 ) {
-    if let Ok((_paddle, paddle_transform, paddle_sprite)) = paddle_query.single() {
-        for (mut ball, ball_transform, ball_sprite) in ball_query.iter_mut() {
+    if let Ok((paddle, paddle_transform, paddle_sprite)) = paddle_query.single() {
+        for (entity, mut ball, ball_transform, ball_sprite) in ball_query.iter_mut() {
             let ball_size = ball_sprite.size;
             let ball_position = ball_transform.translation;
 
@@ -81,18 +137,33 @@ fn ball_paddle_collision(
             let collision = collide(ball_position, ball_size, paddle_position, paddle_size);
 
             if let Some(collision) = collision {
-                audio.play(sounds.paddle.clone());
                 match collision {
                     Collision::Top => {
-                        // adjust velocity on x-axis depending of where it hit on the paddle
-                        let mut velocity = ball.velocity;
-                        velocity.y = velocity.y.abs();
-                        velocity.x += 2.0 * (ball_position.x - paddle_position.x);
+                        // adjust direction depending of where it hit on the paddle
+                        let offset = (ball_position.x - paddle_position.x) / (paddle_size.x / 2.0);
+                        let direction = bounce_direction(
+                            &tuning.paddle_bounce,
+                            offset,
+                            ball.velocity,
+                            paddle.velocity,
+                        );
+                        ball.velocity = ball.speed * direction;
 
                         // for each time it hits the paddle, increase the ball's speed
-                        ball.speed = (ball.speed + 20.0).min(1600.0);
-                        println!("Speed: {:?}", ball.speed);
-                        ball.velocity = ball.speed * velocity.normalize();
+                        ball.on_paddle_hit();
+                        debug!(ball = ball.id.0, speed = %ball.speed, "paddle hit");
+
+                        if paddle.mode == PaddleMode::Sticky {
+                            ball.velocity = Vec3::default();
+                            commands.entity(entity).insert(Attached {
+                                offset: ball_position.x - paddle_position.x,
+                            });
+                        }
+
+                        paddle_hit_events.send(PaddleHit {
+                            ball: ball.id,
+                            position: ball_position,
+                        });
                     }
                     _ => ball.velocity.x *= -1.0,
                 }
@@ -101,21 +172,170 @@ fn ball_paddle_collision(
     }
 }
 
-fn start_ball(
+/// Direction of the ball after bouncing on the paddle.
+/// `offset` is where it hit the paddle, from -1.0 (left edge) to 1.0 (right edge).
+fn bounce_direction(
+    model: &BounceModel,
+    offset: f32,
+    incoming: Vec3,
+    paddle_velocity: f32,
+) -> Vec3 {
+    let offset = offset.clamp(-1.0, 1.0);
+    let angle = match model {
+        BounceModel::Classic { zones } => {
+            let zone = ((offset + 1.0) / 2.0 * zones.len() as f32) as usize;
+            zones[zone.min(zones.len() - 1)]
+        }
+        BounceModel::Continuous {
+            min_angle,
+            max_angle,
+        } => {
+            let angle = offset * max_angle;
+            if angle.abs() < *min_angle {
+                min_angle.copysign(offset)
+            } else {
+                angle
+            }
+        }
+        BounceModel::English { factor, max_angle } => {
+            let x = incoming.x + factor * paddle_velocity;
+            x.atan2(incoming.y.abs())
+                .to_degrees()
+                .clamp(-max_angle, *max_angle)
+        }
+    };
+
+    let angle = angle.to_radians();
+    Vec3::new(angle.sin(), angle.cos(), 0.0)
+}
+
+/// Launches every ball attached to the paddle, the angle depending on where it sits on the paddle
+fn release_balls(
+    mut commands: Commands,
     mouse_button_input: Res<Input<MouseButton>>,
-    mut ball_query: Query<&mut Ball>,
-    paddle_query: Query<(&Paddle, &Transform)>,
+    tuning: Res<Tuning>,
+    mut launched_events: EventWriter<BallLaunched>,
+    mut ball_query: Query<(Entity, &mut Ball, &Attached, &Transform)>,
+    paddle_query: Query<(&Paddle, &Sprite)>,
 ) {
-    if let Ok(mut ball) = ball_query.single_mut() {
-        if mouse_button_input.just_pressed(MouseButton::Left) && ball.velocity == Vec3::default() {
-            let paddle_x = paddle_query
-                .single()
-                .map_or(0.0, |(_paddle, transform)| transform.translation.x);
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
 
+    if let Ok((paddle, paddle_sprite)) = paddle_query.single() {
+        for (entity, mut ball, attached, transform) in ball_query.iter_mut() {
+            let offset = attached.offset / (paddle_sprite.size.x / 2.0);
             let direction =
-                Vec3::new(-paddle_x, CONFIG.paddle_starting_size.width, 0.0).normalize();
-            ball.velocity = CONFIG.ball_starting_speed * direction;
-            ball.speed = CONFIG.ball_starting_speed;
+                bounce_direction(&tuning.paddle_bounce, offset, Vec3::Y, paddle.velocity);
+            if ball.speed == 0.0 {
+                ball.speed = tuning.ball_starting_speed;
+            }
+            ball.velocity = ball.speed * direction;
+            commands.entity(entity).remove::<Attached>();
+            launched_events.send(BallLaunched {
+                position: transform.translation,
+            });
         }
     }
 }
+
+/// Shrinks the paddle the first time any ball reaches the top wall
+fn shrink_paddle(
+    balls_query: Query<&Ball>,
+    mut paddle_query: Query<(&mut Paddle, &mut Sprite, &mut Transform)>,
+) {
+    if let Ok((mut paddle, mut sprite, mut transform)) = paddle_query.single_mut() {
+        if paddle.shrunk || !balls_query.iter().any(|ball| ball.touched_top) {
+            return;
+        }
+
+        paddle.shrunk = true;
+        for rule in CONFIG.speed_rules {
+            if let SpeedRule::TopWall { paddle_shrink, .. } = rule {
+                sprite.size.x *= *paddle_shrink;
+            }
+        }
+
+        let limit = (CONFIG.play_area.width / 2.0) - (sprite.size.x / 2.0);
+        transform.translation.x = transform.translation.x.clamp(-limit, limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// angle of a bounce direction, in degrees from the vertical
+    fn angle(direction: Vec3) -> f32 {
+        direction.x.atan2(direction.y).to_degrees()
+    }
+
+    fn assert_angle(direction: Vec3, expected: f32) {
+        let angle = angle(direction);
+        assert!(
+            (angle - expected).abs() < 1e-3,
+            "expected {} degrees, got {}",
+            expected,
+            angle
+        );
+    }
+
+    #[test]
+    fn continuous_keeps_between_min_and_max() {
+        let model = BounceModel::Continuous {
+            min_angle: 5.0,
+            max_angle: 60.0,
+        };
+        let bounce = |offset| bounce_direction(&model, offset, Vec3::new(0.0, -1.0, 0.0), 0.0);
+
+        assert_angle(bounce(0.5), 30.0);
+        assert_angle(bounce(-0.5), -30.0);
+        // never straight up
+        assert_angle(bounce(0.01), 5.0);
+        assert_angle(bounce(-0.01), -5.0);
+        assert_angle(bounce(1.0), 60.0);
+        assert_angle(bounce(-1.0), -60.0);
+        // past the edges of the paddle
+        assert_angle(bounce(1.5), 60.0);
+        assert_angle(bounce(-1.5), -60.0);
+    }
+
+    #[test]
+    fn classic_picks_the_zone_hit() {
+        let model = BounceModel::Classic {
+            zones: vec![-60.0, -30.0, 30.0, 60.0],
+        };
+        let bounce = |offset| bounce_direction(&model, offset, Vec3::new(0.0, -1.0, 0.0), 0.0);
+
+        // each zone starts at its left edge
+        assert_angle(bounce(-0.51), -60.0);
+        assert_angle(bounce(-0.5), -30.0);
+        assert_angle(bounce(-0.01), -30.0);
+        assert_angle(bounce(0.0), 30.0);
+        assert_angle(bounce(0.49), 30.0);
+        assert_angle(bounce(0.5), 60.0);
+        // the outermost offsets stay in the outermost zones
+        assert_angle(bounce(-1.0), -60.0);
+        assert_angle(bounce(1.0), 60.0);
+        assert_angle(bounce(-3.0), -60.0);
+        assert_angle(bounce(3.0), 60.0);
+    }
+
+    #[test]
+    fn english_clamps_the_paddle_velocity() {
+        let model = BounceModel::English {
+            factor: 0.5,
+            max_angle: 60.0,
+        };
+        let incoming = Vec3::new(-100.0, -100.0, 0.0);
+        let bounce = |paddle_velocity| bounce_direction(&model, 0.0, incoming, paddle_velocity);
+
+        // a still paddle reflects the ball
+        assert_angle(bounce(0.0), -45.0);
+        // moving with the ball picks up its velocity
+        assert_angle(bounce(200.0), 0.0);
+        // a fast paddle can't send the ball flatter than the max angle
+        assert_angle(bounce(2000.0), 60.0);
+        assert_angle(bounce(-2000.0), -60.0);
+    }
+}
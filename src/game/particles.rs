@@ -0,0 +1,98 @@
+use super::{events::BrickDestroyed, Disposable};
+use crate::{settings::Settings, GameState};
+use bevy::prelude::*;
+use rand::{distributions::Uniform, prelude::Distribution};
+
+/// Debris flying out of the destroyed bricks
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ParticleMaterials>();
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .with_system(spawn_debris.system())
+                .with_system(update_particles.system()),
+        );
+    }
+}
+
+const DEBRIS_PER_BRICK: usize = 8;
+const DEBRIS_SIZE: f32 = 6.0;
+const DEBRIS_SECONDS: f32 = 0.5;
+
+struct Particle {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+struct ParticleMaterials {
+    debris: Handle<ColorMaterial>,
+}
+
+impl FromWorld for ParticleMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world
+            .get_resource_mut::<Assets<ColorMaterial>>()
+            .expect("world.assets#colorMaterial");
+        ParticleMaterials {
+            debris: materials.add(Color::rgb(1.0, 0.8, 0.5).into()),
+        }
+    }
+}
+
+fn spawn_debris(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    materials: Res<ParticleMaterials>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+) {
+    if settings.reduced_motion {
+        return;
+    }
+
+    let angles = Uniform::from(0.0..std::f32::consts::TAU);
+    let speeds = Uniform::from(50.0..200.0);
+    let mut rng = rand::thread_rng();
+    for destroyed in destroyed_events.iter() {
+        for _ in 0..DEBRIS_PER_BRICK {
+            let angle: f32 = angles.sample(&mut rng);
+            let velocity = Vec3::new(angle.cos(), angle.sin(), 0.0) * speeds.sample(&mut rng);
+            commands
+                .spawn_bundle(SpriteBundle {
+                    material: materials.debris.clone(),
+                    sprite: Sprite::new(Vec2::new(DEBRIS_SIZE, DEBRIS_SIZE)),
+                    transform: Transform::from_translation(
+                        destroyed.position.truncate().extend(2.0),
+                    ),
+                    ..Default::default()
+                })
+                .insert(Particle {
+                    velocity,
+                    lifetime: Timer::from_seconds(DEBRIS_SECONDS, false),
+                })
+                .insert(Disposable);
+        }
+    }
+}
+
+/// Moves the particles, shrinking them until they are gone
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in particle_query.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation += particle.velocity * time.delta_seconds();
+        let size = DEBRIS_SIZE * particle.lifetime.percent_left();
+        sprite.size = Vec2::new(size, size);
+    }
+}
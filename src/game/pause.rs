@@ -1,103 +1,132 @@
 use bevy::prelude::*;
 
-use crate::{loading::FontAssets, GameState};
+use crate::{
+    localization::Locale,
+    menu::{ButtonMaterials, MenuInput},
+    overlay::{Overlay, OverlayBuilder},
+    GameState,
+};
 
 use super::Disposable;
 
 pub struct PausePlugin;
 impl Plugin for PausePlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.init_resource::<PauseMaterials>();
-
         app.add_system_set(
             SystemSet::on_update(GameState::Game).with_system(handle_keyboard.system()),
         );
 
         app.add_system_set(
-            SystemSet::on_update(GameState::Pause).with_system(handle_keyboard.system()),
+            SystemSet::on_update(GameState::Pause)
+                .after("menu_navigation")
+                .with_system(handle_keyboard.system())
+                .with_system(pause_actions.system()),
         );
     }
 }
 
-struct PauseMaterials {
-    background: Handle<ColorMaterial>,
+struct PauseOverlay;
+
+enum PauseButton {
+    Resume,
+    Quit,
 }
 
-impl FromWorld for PauseMaterials {
-    fn from_world(world: &mut World) -> Self {
-        let mut materials = world
-            .get_resource_mut::<Assets<ColorMaterial>>()
-            .expect("world.assets#colorMaterial");
-        PauseMaterials {
-            background: materials.add(Color::rgba(1.0, 1.0, 1.0, 0.4).into()),
-        }
-    }
+/// Asks before leaving the game for the menu
+struct QuitConfirmation;
+
+enum ConfirmButton {
+    Quit,
+    Cancel,
 }
 
+type PauseOverlays = Or<(With<PauseOverlay>, With<QuitConfirmation>)>;
+
 fn handle_keyboard(
     mut commands: Commands,
     mut keyboard_input: ResMut<Input<KeyCode>>,
     mut state: ResMut<State<GameState>>,
-    font_assets: Res<FontAssets>,
-    pause_materials: Res<PauseMaterials>,
-    text_entity: Query<(Entity, &Text)>,
+    locale: Res<Locale>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    button_materials: Res<ButtonMaterials>,
+    mut overlay_query: Query<&mut Overlay, PauseOverlays>,
 ) {
     if keyboard_input.just_pressed(KeyCode::Space) {
         keyboard_input.reset(KeyCode::Space);
         match state.current() {
             GameState::Pause => {
-                for (entity, _text) in text_entity.iter() {
-                    commands.entity(entity).despawn_recursive();
+                for mut overlay in overlay_query.iter_mut() {
+                    overlay.close(&mut commands);
                 }
 
                 state.pop()
             }
             _ => {
-                commands
-                    .spawn_bundle(NodeBundle {
-                        style: Style {
-                            size: Size::new(Val::Auto, Val::Auto),
-                            position_type: PositionType::Absolute,
-                            position: Rect {
-                                top: Val::Percent(15.0),
-                                left: Val::Percent(10.0),
-                                right: Val::Percent(10.0),
-                                ..Default::default()
-                            },
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::FlexEnd,
-                            ..Default::default()
-                        },
-                        material: pause_materials.background.clone(),
-                        ..Default::default()
-                    })
-                    .with_children(|parent| {
-                        parent.spawn_bundle(TextBundle {
-                            style: Style {
-                                align_self: AlignSelf::Center,
-                                ..Default::default()
-                            },
-                            text: Text {
-                                sections: vec![TextSection {
-                                    value: "PAUSED".to_string(),
-                                    style: TextStyle {
-                                        font: font_assets.text_font.clone(),
-                                        font_size: 72.0,
-                                        color: Color::ORANGE,
-                                    },
-                                }],
-                                alignment: TextAlignment {
-                                    horizontal: HorizontalAlign::Center,
-                                    vertical: VerticalAlign::Center,
-                                },
-                            },
-                            ..Default::default()
-                        });
-                    })
+                OverlayBuilder::new("pause.title")
+                    .subtitle("pause.hint")
+                    .button(PauseButton::Resume, "pause.resume")
+                    .button(PauseButton::Quit, "common.main_menu")
+                    .spawn(&mut commands, &locale, &mut materials, &button_materials)
+                    .insert(PauseOverlay)
                     .insert(Disposable);
                 state.push(GameState::Pause)
             }
         }
         .expect("state: pause");
+    } else if keyboard_input.just_pressed(KeyCode::Escape) && *state.current() == GameState::Game {
+        keyboard_input.reset(KeyCode::Escape);
+        spawn_quit_confirmation(&mut commands, &locale, &mut materials, &button_materials);
+        state.push(GameState::Pause).expect("state: game -> pause");
+    }
+}
+
+fn spawn_quit_confirmation(
+    commands: &mut Commands,
+    locale: &Locale,
+    materials: &mut Assets<ColorMaterial>,
+    button_materials: &ButtonMaterials,
+) {
+    OverlayBuilder::new("quit.title")
+        .subtitle("quit.warning")
+        .button(ConfirmButton::Cancel, "quit.cancel")
+        .button(ConfirmButton::Quit, "quit.confirm")
+        .spawn(commands, locale, materials, button_materials)
+        .insert(QuitConfirmation)
+        .insert(Disposable);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pause_actions(
+    mut commands: Commands,
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    locale: Res<Locale>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    button_materials: Res<ButtonMaterials>,
+    button_query: Query<(Option<&PauseButton>, Option<&ConfirmButton>)>,
+    mut overlay_query: Query<&mut Overlay, PauseOverlays>,
+) {
+    let confirmed = menu_input
+        .confirmed()
+        .and_then(|button| button_query.get(button).ok());
+    match confirmed {
+        Some((Some(PauseButton::Resume), _)) | Some((_, Some(ConfirmButton::Cancel))) => {
+            for mut overlay in overlay_query.iter_mut() {
+                overlay.close(&mut commands);
+            }
+            state.pop().expect("state: pause -> game");
+        }
+        Some((Some(PauseButton::Quit), _)) => {
+            for mut overlay in overlay_query.iter_mut() {
+                overlay.close(&mut commands);
+            }
+            spawn_quit_confirmation(&mut commands, &locale, &mut materials, &button_materials);
+        }
+        Some((_, Some(ConfirmButton::Quit))) => {
+            state
+                .replace(GameState::Menu)
+                .expect("state: pause -> menu");
+        }
+        _ => {}
     }
 }
@@ -0,0 +1,107 @@
+use super::{
+    assists::SafetyNet,
+    balls::Ball,
+    config::CONFIG,
+    events::{BallLost, BrickDestroyed, BrickHit, PaddleHit},
+};
+use crate::{loading::LevelAssets, settings::Settings, GameState};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Keeps the score and the statistics of the run shown on game over
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Run>();
+
+        app.add_system_set(
+            SystemSet::on_update(GameState::Game)
+                .after("collision")
+                .label("run")
+                .with_system(score.system())
+                .with_system(track_run.system()),
+        );
+    }
+}
+
+/// A play through from the first level until game over
+#[derive(Default)]
+pub struct Run {
+    /// lays out the random bricks, retrying a run plays the same levels again
+    pub seed: u64,
+    pub stats: RunStats,
+}
+
+impl Run {
+    /// Random number generator for the bricks of the level with this index
+    pub fn level_rng(&self, level: usize) -> StdRng {
+        StdRng::seed_from_u64(self.seed.wrapping_add(level as u64))
+    }
+}
+
+#[derive(Default)]
+pub struct RunStats {
+    pub score: u32,
+    pub bricks_destroyed: u32,
+    pub max_ball_speed: f32,
+    /// paddle hits since a ball was last lost
+    pub rally: u32,
+    pub longest_rally: u32,
+    /// in seconds, not counting pauses
+    pub time_played: f32,
+    pub levels_cleared: u32,
+    /// an assist was on at some point, see `Settings::assisted`
+    pub assisted: bool,
+}
+
+/// Starts a new run from the first level, retrying one keeps its seed
+pub fn start_run(commands: &mut Commands, level_assets: &mut LevelAssets, seed: u64) {
+    level_assets.index = 0;
+    commands.insert_resource(Run {
+        seed,
+        stats: RunStats::default(),
+    });
+    commands.insert_resource(SafetyNet { armed: true });
+}
+
+fn score(
+    mut run: ResMut<Run>,
+    mut hit_events: EventReader<BrickHit>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+) {
+    let mut hits = 0;
+    for hit in hit_events.iter() {
+        debug!(ball = ?hit.ball.map(|ball| ball.0), "brick hit");
+        hits += 1;
+    }
+    let destroyed = destroyed_events.iter().count() as u32;
+    run.stats.score += hits * CONFIG.brick_hit_score + destroyed * CONFIG.brick_destroyed_score;
+}
+
+fn track_run(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut run: ResMut<Run>,
+    mut destroyed_events: EventReader<BrickDestroyed>,
+    mut paddle_hit_events: EventReader<PaddleHit>,
+    mut ball_lost_events: EventReader<BallLost>,
+    balls_query: Query<&Ball>,
+) {
+    let stats = &mut run.stats;
+    stats.time_played += time.delta_seconds();
+    stats.assisted |= settings.assisted();
+    stats.bricks_destroyed += destroyed_events.iter().count() as u32;
+
+    for _hit in paddle_hit_events.iter() {
+        stats.rally += 1;
+        stats.longest_rally = stats.longest_rally.max(stats.rally);
+    }
+    if ball_lost_events.iter().count() > 0 {
+        stats.rally = 0;
+    }
+
+    for ball in balls_query.iter() {
+        stats.max_ball_speed = stats.max_ball_speed.max(ball.speed);
+    }
+}
@@ -1,8 +1,10 @@
-use super::{config::CONFIG, Ball};
-use crate::{
-    loading::{MaterialsAssets, SoundAssets},
-    GameState,
+use super::{
+    config::{Tuning, CONFIG},
+    events::WallHit,
+    laser::Laser,
+    Ball, Disposable,
 };
+use crate::{loading::MaterialsAssets, GameState};
 use bevy::{
     prelude::*,
     sprite::{
@@ -10,9 +12,36 @@ use bevy::{
         Sprite,
     },
 };
-use bevy_kira_audio::Audio;
 
-struct Wall;
+#[derive(Clone, Copy)]
+pub enum Wall {
+    Left,
+    Right,
+    Top,
+}
+
+impl Wall {
+    /// Position and size of the wall surrounding the play area
+    pub fn layout(&self, thickness: f32) -> (Vec3, Vec2) {
+        let width = CONFIG.play_area.width;
+        let height = CONFIG.play_area.height;
+        match self {
+            Wall::Left => (
+                Vec3::new(-width / 2.0 - thickness / 2.0, 0.0, 0.0),
+                Vec2::new(thickness, height + 2.0 * thickness),
+            ),
+            Wall::Right => (
+                Vec3::new(width / 2.0 + thickness / 2.0, 0.0, 0.0),
+                Vec2::new(thickness, height + 2.0 * thickness),
+            ),
+            Wall::Top => (
+                Vec3::new(0.0, height / 2.0 + thickness / 2.0, 0.0),
+                Vec2::new(width + 2.0 * thickness, thickness),
+            ),
+        }
+    }
+}
+
 pub struct WallPlugin;
 
 impl Plugin for WallPlugin {
@@ -22,67 +51,32 @@ impl Plugin for WallPlugin {
         app.add_system_set(
             SystemSet::on_update(GameState::Game)
                 .after("movement")
-                .with_system(ball_wall_collision.system()),
+                .label("collision")
+                .with_system(ball_wall_collision.system())
+                .with_system(laser_wall_collision.system()),
         );
     }
 }
 
-fn setup_walls(mut commands: Commands, materials: Res<MaterialsAssets>) {
-    // left
-    commands
-        .spawn_bundle(SpriteBundle {
-            material: materials.wall.clone(),
-            transform: Transform::from_xyz(
-                -CONFIG.play_area.width / 2.0 - CONFIG.wall_thickness / 2.0,
-                0.0,
-                0.0,
-            ),
-            sprite: Sprite::new(Vec2::new(
-                CONFIG.wall_thickness,
-                CONFIG.play_area.height + 2.0 * CONFIG.wall_thickness,
-            )),
-            ..Default::default()
-        })
-        .insert(Wall);
-    // right
-    commands
-        .spawn_bundle(SpriteBundle {
-            material: materials.wall.clone(),
-            transform: Transform::from_xyz(
-                CONFIG.play_area.width / 2.0 + CONFIG.wall_thickness / 2.0,
-                0.0,
-                0.0,
-            ),
-            sprite: Sprite::new(Vec2::new(
-                CONFIG.wall_thickness,
-                CONFIG.play_area.height + 2.0 * CONFIG.wall_thickness,
-            )),
-            ..Default::default()
-        })
-        .insert(Wall);
-    // top
-    commands
-        .spawn_bundle(SpriteBundle {
-            material: materials.wall.clone(),
-            transform: Transform::from_xyz(
-                0.0,
-                CONFIG.play_area.height / 2.0 + CONFIG.wall_thickness / 2.0,
-                0.0,
-            ),
-            sprite: Sprite::new(Vec2::new(
-                CONFIG.play_area.width + 2.0 * CONFIG.wall_thickness,
-                CONFIG.wall_thickness,
-            )),
-            ..Default::default()
-        })
-        .insert(Wall);
+fn setup_walls(mut commands: Commands, materials: Res<MaterialsAssets>, tuning: Res<Tuning>) {
+    for wall in [Wall::Left, Wall::Right, Wall::Top].iter() {
+        let (translation, size) = wall.layout(tuning.wall_thickness);
+        commands
+            .spawn_bundle(SpriteBundle {
+                material: materials.wall.clone(),
+                transform: Transform::from_translation(translation),
+                sprite: Sprite::new(size),
+                ..Default::default()
+            })
+            .insert(*wall)
+            .insert(Disposable);
+    }
 }
 
 fn ball_wall_collision(
     mut ball_query: Query<(&mut Ball, &Transform, &Sprite)>,
     wall_query: Query<(&Wall, &Transform, &Sprite)>,
-    audio: Res<Audio>,
-    sounds: Res<SoundAssets>,
+    mut wall_hit_events: EventWriter<WallHit>,
 ) {
     for (mut ball, ball_transform, ball_sprite) in ball_query.iter_mut() {
         let ball_size = ball_sprite.size;
@@ -92,7 +86,9 @@ fn ball_wall_collision(
             let collision = collide(ball_position, ball_size, transform.translation, sprite.size);
 
             if let Some(collision) = collision {
-                audio.play(sounds.hit.clone());
+                wall_hit_events.send(WallHit {
+                    position: ball_position,
+                });
                 match collision {
                     Collision::Left | Collision::Right => {
                         ball.velocity.x = ball.velocity.x.copysign(-ball_position.x)
@@ -103,3 +99,25 @@ fn ball_wall_collision(
         }
     }
 }
+
+fn laser_wall_collision(
+    mut commands: Commands,
+    laser_query: Query<(Entity, &Laser, &Transform, &Sprite)>,
+    wall_query: Query<(&Wall, &Transform, &Sprite)>,
+) {
+    for (entity, _laser, laser_transform, laser_sprite) in laser_query.iter() {
+        let hit_wall = wall_query.iter().any(|(_wall, transform, sprite)| {
+            collide(
+                laser_transform.translation,
+                laser_sprite.size,
+                transform.translation,
+                sprite.size,
+            )
+            .is_some()
+        });
+
+        if hit_wall {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,139 +1,596 @@
-use crate::GameState;
-use bevy::{asset::LoadState, prelude::*};
+use crate::{
+    achievements::AchievementList,
+    game::{config::Tuning, level::Level},
+    localization::{LocaleAssets, LocaleFile, LANGUAGES},
+    manifest::{AssetManifest, MusicCues},
+    profiles::Profiles,
+    ron_asset::RonAssetLoader,
+    GameState,
+};
+use bevy::{asset::LoadState, prelude::*, render::pass::ClearColor};
 use bevy_kira_audio::AudioSource;
+use rand::Rng;
+use std::collections::HashMap;
 
 pub struct LoadingPlugin;
 
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_set(
-            SystemSet::on_enter(GameState::Loading).with_system(start_loading.system()),
-        )
-        .add_system_set(SystemSet::on_update(GameState::Loading).with_system(check_state.system()));
-    }
-}
-
-struct AssetPaths {
-    zen_dots: &'static str,
-    ball: &'static str,
-    paddle: &'static str,
-    bricks: &'static str,
-    hit_sound: &'static str,
-    explosion_sound: &'static str,
-    paddle_sound: &'static str,
-    music: &'static str,
-}
-
-const PATHS: AssetPaths = AssetPaths {
-    zen_dots: "fonts/ZenDots-Regular.ttf",
-    ball: "images/silver-ball.png",
-    paddle: "images/wood-texture.png",
-    bricks: "images/bricks.png",
-    hit_sound: "sounds/hit.mp3",
-    explosion_sound: "sounds/explosion.mp3",
-    paddle_sound: "sounds/paddle.mp3",
-    music: "sounds/Testament - Over The Wall (8-Bit Version).mp3",
-};
+        app.add_asset::<AssetManifest>()
+            .init_asset_loader::<RonAssetLoader<AssetManifest>>()
+            .add_asset::<LocaleFile>()
+            .init_asset_loader::<RonAssetLoader<LocaleFile>>()
+            .init_resource::<LoadingMaterials>()
+            .init_resource::<SelectedTheme>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Loading).with_system(start_loading.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Loading)
+                    .with_system(load_manifest_assets.system())
+                    .with_system(update_progress.system())
+                    .with_system(check_state.system()),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Loading).with_system(cleanup.system()));
+    }
+}
+
+/// Name and manifest of the themes that can be picked in the options, the first is the default
+pub const THEMES: [(&str, &str); 2] = [
+    ("Classic", "themes/classic.manifest.ron"),
+    ("Neon", "themes/neon.manifest.ron"),
+];
+
+/// Index in `THEMES`, the assets are reloaded by going back to `GameState::Loading`
+#[derive(Default)]
+pub struct SelectedTheme(pub usize);
+
+/// font of the loading screen itself, everything else comes from the manifest
+const LOADING_FONT: &str = "fonts/ZenDots-Regular.ttf";
+
+const GROUPS: [&str; 6] = ["manifest", "data", "fonts", "images", "sounds", "music"];
+
+/// gameplay tuning, shared by every theme
+const TUNING: &str = "game.config.ron";
+
+/// shared by every theme too
+const ACHIEVEMENTS: &str = "game.achievements.ron";
+
+/// levels in the order they are played
+const LEVELS: [&str; 1] = ["levels/01.level.ron"];
 
 pub struct MaterialsAssets {
     pub paddle: Handle<ColorMaterial>,
     pub wall: Handle<ColorMaterial>,
     pub ball: Handle<ColorMaterial>,
+    pub laser: Handle<ColorMaterial>,
+}
+
+/// The materials as the theme made them, high contrast replaces them while it's on
+pub struct ThemeMaterials {
+    pub paddle: ThemeMaterial,
+    pub wall: ThemeMaterial,
+    pub ball: ThemeMaterial,
+    pub laser: ThemeMaterial,
+}
+
+pub struct ThemeMaterial {
+    color: Color,
+    texture: Option<Handle<Texture>>,
+}
+
+impl ThemeMaterial {
+    pub fn material(&self) -> ColorMaterial {
+        ColorMaterial {
+            color: self.color,
+            texture: self.texture.clone(),
+        }
+    }
 }
 
 pub struct BrickAssets {
     pub textures: Handle<TextureAtlas>,
+    pub cell_size: Vec2,
+    /// one frame per brick life
+    pub frames: u32,
+}
+
+pub struct LevelAssets {
+    pub levels: Vec<Handle<Level>>,
+    /// index in `levels` of the one being played
+    pub index: usize,
+}
+
+impl LevelAssets {
+    pub fn current(&self) -> &Handle<Level> {
+        &self.levels[self.index]
+    }
+
+    /// Moves on to the following level, back to the first after the last one
+    pub fn next(&mut self) {
+        self.index = (self.index + 1) % self.levels.len();
+    }
 }
+
 pub struct FontAssets {
     pub text_font: Handle<Font>,
+    /// used instead of the text font for a language it lacks characters for
+    pub fallback_font: Option<Handle<Font>>,
 }
 
 pub struct SoundAssets {
-    pub hit: Handle<AudioSource>,
-    pub music: Handle<AudioSource>,
-    pub explosion: Handle<AudioSource>,
-    pub paddle: Handle<AudioSource>,
+    pub hit: SoundBank,
+    pub explosion: SoundBank,
+    pub paddle: SoundBank,
+    pub laser: SoundBank,
+    /// the audio cues, see `game::audio_cues`
+    pub cue_tone: SoundBank,
+    pub cue_launch: SoundBank,
+    pub cue_lost: SoundBank,
+    pub cue_power_up: SoundBank,
+}
+
+pub struct MusicAssets {
+    /// only the tracks that could be loaded, the game plays without the missing ones
+    tracks: HashMap<String, Handle<AudioSource>>,
+    pub cues: MusicCues,
+}
+
+impl MusicAssets {
+    pub fn track(&self, name: &str) -> Option<Handle<AudioSource>> {
+        self.tracks.get(name).cloned()
+    }
+}
+
+/// Variations of the same sound
+pub struct SoundBank {
+    variations: Vec<Handle<AudioSource>>,
+}
+
+impl SoundBank {
+    fn new(asset_server: &AssetServer, paths: &[String]) -> Self {
+        SoundBank {
+            variations: paths
+                .iter()
+                .map(|path| asset_server.get_handle(path.as_str()))
+                .collect(),
+        }
+    }
+
+    /// one of the variations, at random
+    pub fn pick(&self) -> Handle<AudioSource> {
+        let index = rand::thread_rng().gen_range(0..self.variations.len());
+        self.variations[index].clone()
+    }
+}
+
+struct LoadingMaterials {
+    background: Handle<ColorMaterial>,
+    bar_background: Handle<ColorMaterial>,
+    bar: Handle<ColorMaterial>,
+}
+
+impl FromWorld for LoadingMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world
+            .get_resource_mut::<Assets<ColorMaterial>>()
+            .expect("world.assets#colorMaterial");
+        LoadingMaterials {
+            background: materials.add(Color::NONE.into()),
+            bar_background: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
+            bar: materials.add(Color::ORANGE.into()),
+        }
+    }
+}
+
+struct AssetGroup {
+    /// the game can run without these assets
+    optional: bool,
+    assets: Vec<(String, HandleUntyped)>,
+}
+
+impl AssetGroup {
+    fn load<'a>(
+        asset_server: &AssetServer,
+        optional: bool,
+        paths: impl Iterator<Item = &'a String>,
+    ) -> Self {
+        AssetGroup {
+            optional,
+            assets: paths
+                .map(|path| (path.clone(), asset_server.load_untyped(path.as_str())))
+                .collect(),
+        }
+    }
+
+    fn load_states<'a>(
+        &'a self,
+        asset_server: &'a AssetServer,
+    ) -> impl Iterator<Item = (&'a str, LoadState)> + 'a {
+        self.assets
+            .iter()
+            .map(move |(path, handle)| (path.as_str(), asset_server.get_load_state(handle.id)))
+    }
+
+    /// fraction of the assets that finished loading, failed ones included
+    fn progress(&self, asset_server: &AssetServer) -> f32 {
+        let done = self
+            .load_states(asset_server)
+            .filter(|(_path, state)| matches!(state, LoadState::Loaded | LoadState::Failed))
+            .count();
+        done as f32 / self.assets.len().max(1) as f32
+    }
+
+    fn failed<'a>(&'a self, asset_server: &'a AssetServer) -> Vec<&'a str> {
+        self.load_states(asset_server)
+            .filter(|(_path, state)| *state == LoadState::Failed)
+            .map(|(path, _state)| path)
+            .collect()
+    }
+
+    fn is_ready(&self, asset_server: &AssetServer) -> bool {
+        self.load_states(asset_server).all(|(_path, state)| {
+            state == LoadState::Loaded || (self.optional && state == LoadState::Failed)
+        })
+    }
 }
 
 struct LoadingState {
-    images: Vec<HandleUntyped>,
-    fonts: Vec<HandleUntyped>,
-    sounds: Vec<HandleUntyped>,
+    manifest: Handle<AssetManifest>,
+    tuning: Handle<Tuning>,
+    achievements: Handle<AchievementList>,
+    levels: Vec<Handle<Level>>,
+    locales: Vec<Handle<LocaleFile>>,
+    /// in the same order as `GROUPS`, the ones after the manifest are added once it's loaded
+    groups: Vec<AssetGroup>,
+    /// names the game needs that the manifest lacks, the game can't start without them
+    missing_names: Vec<String>,
 }
 
-fn start_loading(mut commands: Commands, asset_server: Res<AssetServer>) {
-    println!("start_loading");
-    let mut fonts: Vec<HandleUntyped> = vec![];
-    fonts.push(asset_server.load_untyped(PATHS.zen_dots));
+struct LoadingScreen;
+
+/// Fill of the progress bar of the asset group with this index
+struct ProgressBar(usize);
+
+struct LoadingError;
+
+fn start_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<LoadingMaterials>,
+    theme: Res<SelectedTheme>,
+) {
+    let (theme_name, manifest_path) = THEMES[theme.0];
+    info!(theme = theme_name, "start loading");
+    let manifest = asset_server.load(manifest_path);
+    let bootstrap = [manifest_path.to_string(), LOADING_FONT.to_string()];
+    let tuning = asset_server.load(TUNING);
+    let achievements = asset_server.load(ACHIEVEMENTS);
+    let levels = LEVELS.iter().map(|path| asset_server.load(*path)).collect();
+    let locales = LANGUAGES
+        .iter()
+        .map(|(_, path)| asset_server.load(*path))
+        .collect();
+    let data: Vec<String> = [TUNING, ACHIEVEMENTS]
+        .iter()
+        .copied()
+        .chain(LEVELS.iter().copied())
+        .chain(LANGUAGES.iter().map(|(_, path)| *path))
+        .map(String::from)
+        .collect();
+    let groups = vec![
+        AssetGroup::load(&asset_server, false, bootstrap.iter()),
+        AssetGroup::load(&asset_server, false, data.iter()),
+    ];
+
+    // the text shows up as soon as the font is loaded
+    let text_style = TextStyle {
+        font: asset_server.get_handle(LOADING_FONT),
+        font_size: 20.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
 
-    let mut images: Vec<HandleUntyped> = vec![];
-    images.push(asset_server.load_untyped(PATHS.ball));
-    images.push(asset_server.load_untyped(PATHS.paddle));
-    images.push(asset_server.load_untyped(PATHS.bricks));
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.background.clone(),
+            ..Default::default()
+        })
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            for (index, name) in GROUPS.iter().enumerate() {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.0)),
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        material: materials.background.clone(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(120.0), Val::Auto),
+                                ..Default::default()
+                            },
+                            text: Text::with_section(*name, text_style.clone(), Default::default()),
+                            ..Default::default()
+                        });
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(300.0), Val::Px(20.0)),
+                                    ..Default::default()
+                                },
+                                material: materials.bar_background.clone(),
+                                ..Default::default()
+                            })
+                            .with_children(|parent| {
+                                parent
+                                    .spawn_bundle(NodeBundle {
+                                        style: Style {
+                                            size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                                            ..Default::default()
+                                        },
+                                        material: materials.bar.clone(),
+                                        ..Default::default()
+                                    })
+                                    .insert(ProgressBar(index));
+                            });
+                    });
+            }
 
-    let mut sounds: Vec<HandleUntyped> = vec![];
-    sounds.push(asset_server.load_untyped(PATHS.hit_sound));
-    sounds.push(asset_server.load_untyped(PATHS.music));
-    sounds.push(asset_server.load_untyped(PATHS.explosion_sound));
-    sounds.push(asset_server.load_untyped(PATHS.paddle_sound));
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(20.0)),
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            color: Color::RED,
+                            ..text_style.clone()
+                        },
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            vertical: VerticalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(LoadingError);
+        });
 
     commands.insert_resource(LoadingState {
-        images,
-        fonts,
-        sounds,
+        manifest,
+        tuning,
+        achievements,
+        levels,
+        locales,
+        groups,
+        missing_names: vec![],
     });
 }
 
+fn load_manifest_assets(
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<AssetManifest>>,
+    mut loading_state: ResMut<LoadingState>,
+) {
+    if loading_state.groups.len() > 2 {
+        return;
+    }
+
+    if let Some(manifest) = manifests.get(&loading_state.manifest) {
+        loading_state.missing_names = manifest.missing();
+        for name in loading_state.missing_names.iter() {
+            error!(%name, "missing from the manifest");
+        }
+
+        let fonts = AssetGroup::load(&asset_server, false, manifest.fonts.values());
+        let images = AssetGroup::load(
+            &asset_server,
+            false,
+            manifest
+                .textures
+                .values()
+                .chain(manifest.atlases.values().map(|atlas| &atlas.path)),
+        );
+        let sounds = AssetGroup::load(&asset_server, false, manifest.sounds.values().flatten());
+        let music = AssetGroup::load(&asset_server, true, manifest.music.values());
+        loading_state
+            .groups
+            .extend(vec![fonts, images, sounds, music]);
+    }
+}
+
+fn update_progress(
+    asset_server: Res<AssetServer>,
+    loading_state: Res<LoadingState>,
+    mut reported: Local<Vec<String>>,
+    mut bar_query: Query<(&ProgressBar, &mut Style)>,
+    mut error_query: Query<&mut Text, With<LoadingError>>,
+) {
+    for (bar, mut style) in bar_query.iter_mut() {
+        let progress = loading_state
+            .groups
+            .get(bar.0)
+            .map_or(0.0, |group| group.progress(&asset_server));
+        style.size.width = Val::Percent(100.0 * progress);
+    }
+
+    let mut missing = vec![];
+    for group in loading_state.groups.iter() {
+        for path in group.failed(&asset_server) {
+            if !reported.iter().any(|reported| reported == path) {
+                reported.push(path.to_string());
+                if group.optional {
+                    warn!(path, "missing optional asset, skipping it");
+                } else {
+                    error!(path, "failed to load asset");
+                }
+            }
+            if !group.optional {
+                missing.push(path);
+            }
+        }
+    }
+
+    let mut errors = vec![];
+    if !missing.is_empty() {
+        errors.push(format!("Failed to load:\n{}", missing.join("\n")));
+    }
+    if !loading_state.missing_names.is_empty() {
+        errors.push(format!(
+            "Missing from the theme:\n{}",
+            loading_state.missing_names.join("\n")
+        ));
+    }
+    if !errors.is_empty() {
+        for mut text in error_query.iter_mut() {
+            text.sections[0].value = errors.join("\n\n");
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn check_state(
     mut commands: Commands,
     mut state: ResMut<State<GameState>>,
     asset_server: Res<AssetServer>,
+    manifests: Res<Assets<AssetManifest>>,
+    tunings: Res<Assets<Tuning>>,
+    achievements: Res<Assets<AchievementList>>,
+    profiles: Res<Profiles>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     loading_state: Res<LoadingState>,
 ) {
-    if !has_loaded(&asset_server, &loading_state.fonts) {
+    let ready = loading_state.groups.len() == GROUPS.len()
+        && loading_state.missing_names.is_empty()
+        && loading_state
+            .groups
+            .iter()
+            .all(|group| group.is_ready(&asset_server));
+    if !ready {
         return;
     }
 
-    if !has_loaded(&asset_server, &loading_state.images) {
-        return;
-    }
+    let manifest = manifests
+        .get(&loading_state.manifest)
+        .expect("loading: manifest");
 
-    if !has_loaded(&asset_server, &loading_state.sounds) {
-        return;
-    }
+    let tuning = tunings.get(&loading_state.tuning).expect("loading: tuning");
+    commands.insert_resource(tuning.clone());
+    let achievements = achievements
+        .get(&loading_state.achievements)
+        .expect("loading: achievements");
+    commands.insert_resource(achievements.clone());
+    commands.insert_resource(LevelAssets {
+        levels: loading_state.levels.clone(),
+        index: 0,
+    });
 
     commands.insert_resource(FontAssets {
-        text_font: asset_server.get_handle(PATHS.zen_dots),
+        text_font: asset_server.get_handle(manifest.font("text").expect("loading: font")),
+        fallback_font: manifest
+            .fonts
+            .get("fallback")
+            .map(|path| asset_server.get_handle(path.as_str())),
+    });
+    commands.insert_resource(LocaleAssets {
+        files: loading_state.locales.clone(),
     });
 
+    commands.insert_resource(ClearColor(
+        manifest.color("background").unwrap_or(Color::BLACK),
+    ));
+
+    let theme_materials = ThemeMaterials {
+        ball: theme_material(&asset_server, manifest, "ball"),
+        paddle: theme_material(&asset_server, manifest, "paddle"),
+        wall: theme_material(&asset_server, manifest, "wall"),
+        laser: theme_material(&asset_server, manifest, "laser"),
+    };
     commands.insert_resource(MaterialsAssets {
-        ball: materials.add(asset_server.get_handle(PATHS.ball).into()),
-        paddle: materials.add(asset_server.get_handle(PATHS.paddle).into()),
-        wall: materials.add(Color::GRAY.into()),
+        ball: materials.add(theme_materials.ball.material()),
+        paddle: materials.add(theme_materials.paddle.material()),
+        wall: materials.add(theme_materials.wall.material()),
+        laser: materials.add(theme_materials.laser.material()),
     });
+    commands.insert_resource(theme_materials);
 
+    let bricks = manifest.atlas("bricks").expect("loading: atlas");
+    let cell_size = Vec2::new(bricks.cell_size.0, bricks.cell_size.1);
     commands.insert_resource(BrickAssets {
         textures: texture_atlases.add(TextureAtlas::from_grid(
-            asset_server.get_handle(PATHS.bricks),
-            Vec2::new(200.0, 70.0),
-            3,
-            1,
+            asset_server.get_handle(bricks.path.as_str()),
+            cell_size,
+            bricks.columns,
+            bricks.rows,
         )),
+        cell_size,
+        frames: (bricks.columns * bricks.rows) as u32,
     });
 
+    // every name the game looks up was checked with `AssetManifest::missing`
+    let sound = |name| SoundBank::new(&asset_server, manifest.sound(name).expect("loading: sound"));
     commands.insert_resource(SoundAssets {
-        hit: asset_server.get_handle(PATHS.hit_sound),
-        explosion: asset_server.get_handle(PATHS.explosion_sound),
-        paddle: asset_server.get_handle(PATHS.paddle_sound),
-        music: asset_server.get_handle(PATHS.music),
+        hit: sound("hit"),
+        explosion: sound("explosion"),
+        paddle: sound("paddle"),
+        laser: sound("laser"),
+        cue_tone: sound("cue_tone"),
+        cue_launch: sound("cue_launch"),
+        cue_lost: sound("cue_lost"),
+        cue_power_up: sound("cue_power_up"),
     });
+    commands.insert_resource(MusicAssets {
+        tracks: manifest
+            .music
+            .iter()
+            .filter(|(_, path)| asset_server.get_load_state(path.as_str()) == LoadState::Loaded)
+            .map(|(name, path)| (name.clone(), asset_server.get_handle(path.as_str())))
+            .collect(),
+        cues: manifest.music_cues.clone(),
+    });
+
+    if profiles.picked {
+        state.set(GameState::Menu).expect("state: loading -> menu");
+    } else {
+        state
+            .set(GameState::Profiles)
+            .expect("state: loading -> profiles");
+    }
+    info!("finish loading");
+}
 
-    state.set(GameState::Menu).expect("state: loading -> menu");
-    println!("finish loading");
+/// The texture with that name tinted by the color with the same name, either can be missing
+fn theme_material(
+    asset_server: &AssetServer,
+    manifest: &AssetManifest,
+    name: &str,
+) -> ThemeMaterial {
+    ThemeMaterial {
+        color: manifest.color(name).unwrap_or(Color::WHITE),
+        texture: manifest
+            .textures
+            .get(name)
+            .map(|path| asset_server.get_handle(path.as_str())),
+    }
 }
 
-fn has_loaded(asset_server: &Res<AssetServer>, assets: &Vec<HandleUntyped>) -> bool {
-    LoadState::Loaded == asset_server.get_group_load_state(assets.iter().map(|handle| handle.id))
+fn cleanup(mut commands: Commands, screen_query: Query<(Entity, &LoadingScreen)>) {
+    for (entity, _screen) in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
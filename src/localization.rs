@@ -0,0 +1,198 @@
+use crate::{loading::FontAssets, ron_asset::RonAsset, settings::Settings, GameState};
+use ab_glyph::Font as _;
+use bevy::{app::CoreStage, prelude::*, reflect::TypeUuid};
+use serde::Deserialize;
+use std::{borrow::Cow, collections::HashMap};
+
+/// Shows the texts in the language picked in the options, switching them all when it changes
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Locale>()
+            // before the screen after the loading one is set up
+            .add_system_set(
+                SystemSet::on_exit(GameState::Loading).with_system(load_locale.system()),
+            )
+            .add_system(switch_language.system())
+            .add_system_to_stage(CoreStage::PostUpdate, localize_texts.system());
+    }
+}
+
+/// Code and file of each language, English is the first and fills in any missing string
+pub const LANGUAGES: [(&str, &str); 5] = [
+    ("en", "locales/en.locale.ron"),
+    ("fr", "locales/fr.locale.ron"),
+    ("de", "locales/de.locale.ron"),
+    ("es", "locales/es.locale.ron"),
+    ("ru", "locales/ru.locale.ron"),
+];
+
+/// The strings of a language, read from the `.locale.ron` files in `assets/locales`
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "3c8e5f4a-1d2b-4e6f-9a7c-8b0d2e4f6a13"]
+pub struct LocaleFile {
+    /// `{name}` in a string is replaced by the argument with that name
+    pub strings: HashMap<String, String>,
+}
+
+impl RonAsset for LocaleFile {
+    const EXTENSIONS: &'static [&'static str] = &["locale.ron"];
+}
+
+/// The locale files, in the same order as `LANGUAGES`
+pub struct LocaleAssets {
+    pub files: Vec<Handle<LocaleFile>>,
+}
+
+/// Strings of the current language
+#[derive(Default)]
+pub struct Locale {
+    strings: HashMap<String, String>,
+    /// the theme's font, or the fallback one when it misses some characters of the language
+    pub font: Handle<Font>,
+}
+
+impl Locale {
+    pub fn text(&self, localized: &Localized) -> String {
+        let mut text = match &localized.source {
+            Source::Key(key) => self
+                .strings
+                .get(key.as_ref())
+                .cloned()
+                .unwrap_or_else(|| key.to_string()),
+            Source::Verbatim(text) => text.clone(),
+        };
+        for (name, arg) in localized.args.iter() {
+            text = text.replace(&format!("{{{}}}", name), &self.text(arg));
+        }
+        text
+    }
+}
+
+/// A text translated to the current language, set on a `Text` it replaces its first section.
+/// Made from a `&'static str` it's a key in the locale files, from a `String` it's shown as is.
+#[derive(Clone)]
+pub struct Localized {
+    source: Source,
+    args: Vec<(&'static str, Localized)>,
+}
+
+#[derive(Clone)]
+enum Source {
+    Key(Cow<'static, str>),
+    Verbatim(String),
+}
+
+impl Localized {
+    /// A key made at runtime, e.g. from the id of something defined in a data file
+    pub fn key(key: String) -> Self {
+        Localized {
+            source: Source::Key(Cow::Owned(key)),
+            args: vec![],
+        }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl Into<Localized>) -> Self {
+        self.args.push((name, value.into()));
+        self
+    }
+}
+
+impl From<&'static str> for Localized {
+    fn from(key: &'static str) -> Self {
+        Localized {
+            source: Source::Key(Cow::Borrowed(key)),
+            args: vec![],
+        }
+    }
+}
+
+impl From<String> for Localized {
+    fn from(text: String) -> Self {
+        Localized {
+            source: Source::Verbatim(text),
+            args: vec![],
+        }
+    }
+}
+
+fn load_locale(
+    settings: Res<Settings>,
+    locale_assets: Res<LocaleAssets>,
+    locale_files: Res<Assets<LocaleFile>>,
+    font_assets: Res<FontAssets>,
+    fonts: Res<Assets<Font>>,
+    mut locale: ResMut<Locale>,
+) {
+    let index = LANGUAGES
+        .iter()
+        .position(|(code, _)| *code == settings.language)
+        .unwrap_or(0);
+    let file = |index: usize| {
+        locale_files
+            .get(&locale_assets.files[index])
+            .expect("localization: locale")
+    };
+    let mut strings = file(0).strings.clone();
+    strings.extend(file(index).strings.clone());
+
+    let covered = |font: &Handle<Font>| match fonts.get(font) {
+        Some(font) => strings
+            .values()
+            .flat_map(|string| string.chars())
+            .filter(|character| !character.is_whitespace())
+            .all(|character| font.font.glyph_id(character).0 != 0),
+        None => true,
+    };
+    let font = match &font_assets.fallback_font {
+        Some(fallback) if !covered(&font_assets.text_font) => {
+            info!(language = %settings.language, "falling back to another font");
+            fallback.clone()
+        }
+        _ => font_assets.text_font.clone(),
+    };
+
+    *locale = Locale { strings, font };
+}
+
+fn switch_language(
+    settings: Res<Settings>,
+    locale_assets: Option<Res<LocaleAssets>>,
+    locale_files: Res<Assets<LocaleFile>>,
+    font_assets: Option<Res<FontAssets>>,
+    fonts: Res<Assets<Font>>,
+    locale: ResMut<Locale>,
+    mut language: Local<String>,
+) {
+    if *language == settings.language {
+        return;
+    }
+    // loaded with the rest of the assets the first time
+    if let (Some(locale_assets), Some(font_assets)) = (locale_assets, font_assets) {
+        *language = settings.language.clone();
+        load_locale(
+            settings,
+            locale_assets,
+            locale_files,
+            font_assets,
+            fonts,
+            locale,
+        );
+    }
+}
+
+fn localize_texts(
+    locale: Res<Locale>,
+    mut text_query: Query<(&Localized, &mut Text, ChangeTrackers<Localized>)>,
+) {
+    for (localized, mut text, tracker) in text_query.iter_mut() {
+        if !locale.is_changed() && !tracker.is_changed() {
+            continue;
+        }
+        text.sections[0].value = locale.text(localized);
+        for section in text.sections.iter_mut() {
+            section.style.font = locale.font.clone();
+        }
+    }
+}
@@ -1,16 +1,36 @@
 // disable console opening on windows
 // #![windows_subsystem = "windows"]
 
+mod accessibility;
+mod achievements;
+mod camera;
 mod game;
 mod loading;
+mod localization;
+mod manifest;
 mod menu;
+mod music;
+mod options;
+mod overlay;
+mod profiles;
+mod ron_asset;
+mod settings;
 
+use accessibility::AccessibilityPlugin;
+use achievements::AchievementsPlugin;
 use bevy::prelude::*;
 use bevy::render::pass::ClearColor;
-use bevy_kira_audio::{Audio, AudioChannel, AudioPlugin};
-use game::{config::CONFIG, GamePlugin};
-use loading::{LoadingPlugin, SoundAssets};
+use bevy_kira_audio::AudioPlugin;
+use camera::CameraPlugin;
+use game::GamePlugin;
+use loading::LoadingPlugin;
+use localization::LocalizationPlugin;
 use menu::MenuPlugin;
+use music::MusicPlugin;
+use options::OptionsPlugin;
+use overlay::OverlayPlugin;
+use profiles::ProfilesPlugin;
+use settings::{Settings, SettingsPlugin};
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 enum GameState {
@@ -19,44 +39,37 @@ enum GameState {
     GameOver,
     Pause,
     Menu,
+    Options,
+    LevelComplete,
+    Achievements,
+    Profiles,
+    Stats,
 }
 
 fn main() {
+    // read before the window is created so it opens with the saved display settings
+    let settings = Settings::load();
     App::build()
-        .insert_resource(Msaa { samples: 4 })
-        .insert_resource(ClearColor(Color::BLACK))
-        .insert_resource(WindowDescriptor {
-            title: "Breakout".to_string(),
-            width: CONFIG.play_area.width + 2.0 * CONFIG.wall_thickness,
-            height: CONFIG.play_area.height + 2.0 * CONFIG.wall_thickness,
-            resizable: false,
-            vsync: false,
-            ..Default::default()
+        .insert_resource(Msaa {
+            samples: settings.msaa_samples,
         })
-        .add_startup_system(load_cameras.system())
+        .insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(settings.window_descriptor())
+        .insert_resource(settings)
         .add_plugins(DefaultPlugins)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(AccessibilityPlugin)
+        .add_plugin(CameraPlugin)
         .add_plugin(AudioPlugin)
+        .add_plugin(MusicPlugin)
         .add_plugin(LoadingPlugin)
+        .add_plugin(LocalizationPlugin)
         .add_plugin(MenuPlugin)
+        .add_plugin(OptionsPlugin)
+        .add_plugin(OverlayPlugin)
+        .add_plugin(AchievementsPlugin)
+        .add_plugin(ProfilesPlugin)
         .add_plugin(GamePlugin)
         .add_state(GameState::Loading)
-        .insert_resource(MusicChannel {
-            music: AudioChannel::new("music".to_owned()),
-        })
-        .add_system_set(SystemSet::on_exit(GameState::Loading).with_system(play_music.system()))
         .run();
 }
-
-fn load_cameras(mut commands: Commands) {
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
-    commands.spawn_bundle(UiCameraBundle::default());
-}
-
-struct MusicChannel {
-    music: AudioChannel,
-}
-
-fn play_music(sounds: Res<SoundAssets>, audio: Res<Audio>, channels: Res<MusicChannel>) {
-    audio.set_volume_in_channel(0.7, &channels.music);
-    audio.play_looped_in_channel(sounds.music.clone(), &channels.music);
-}
@@ -0,0 +1,160 @@
+use crate::ron_asset::RonAsset;
+use bevy::{prelude::Color, reflect::TypeUuid};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Names the game looks up in every manifest, see `AssetManifest::missing`
+const FONTS: [&str; 1] = ["text"];
+const ATLASES: [&str; 1] = ["bricks"];
+const SOUNDS: [&str; 8] = [
+    "hit",
+    "explosion",
+    "paddle",
+    "laser",
+    "cue_tone",
+    "cue_launch",
+    "cue_lost",
+    "cue_power_up",
+];
+
+/// Every asset the game uses, by logical name. Each theme in `assets/themes` is a manifest.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "6f1d8a52-3c1b-4f0e-9a57-2b9f1c1e7d43"]
+pub struct AssetManifest {
+    pub textures: HashMap<String, String>,
+    pub atlases: HashMap<String, AtlasManifest>,
+    pub fonts: HashMap<String, String>,
+    /// each sound is a bank of variations
+    pub sounds: HashMap<String, Vec<String>>,
+    /// rgba, tints the texture with the same name or is used on its own when there's none
+    #[serde(default)]
+    pub colors: HashMap<String, [f32; 4]>,
+    #[serde(default)]
+    pub music: HashMap<String, String>,
+    #[serde(default)]
+    pub music_cues: MusicCues,
+}
+
+/// Track played in each part of the game, by name in `music`.
+/// A level can play its own track instead of `game`, see `Level::music`.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MusicCues {
+    pub menu: String,
+    pub game: String,
+    /// jingles are played once, the previous track keeps playing when there's none
+    pub game_over: Option<String>,
+    pub level_complete: Option<String>,
+    /// stems played along `game`, each fades in once the intensity reaches its own
+    pub game_layers: Vec<MusicLayer>,
+    /// the game music plays this much faster at full intensity, 0.1 for 10%
+    pub tempo_boost: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MusicLayer {
+    pub track: String,
+    /// from 0.0 to 1.0, see `music::MusicIntensity`
+    pub intensity: f32,
+}
+
+impl Default for MusicCues {
+    fn default() -> Self {
+        MusicCues {
+            menu: "main".to_string(),
+            game: "main".to_string(),
+            game_over: None,
+            level_complete: None,
+            game_layers: Vec::new(),
+            tempo_boost: 0.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AtlasManifest {
+    pub path: String,
+    pub cell_size: (f32, f32),
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl AssetManifest {
+    pub fn atlas(&self, name: &str) -> Option<&AtlasManifest> {
+        self.atlases.get(name)
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).map(|color| Color::from(*color))
+    }
+
+    pub fn font(&self, name: &str) -> Option<&str> {
+        self.fonts.get(name).map(String::as_str)
+    }
+
+    /// `None` for a bank without any file too
+    pub fn sound(&self, name: &str) -> Option<&[String]> {
+        self.sounds
+            .get(name)
+            .map(Vec::as_slice)
+            .filter(|bank| !bank.is_empty())
+    }
+
+    /// The names the game needs that aren't in the manifest, e.g. `sound 'hit'`
+    pub fn missing(&self) -> Vec<String> {
+        let fonts = FONTS
+            .iter()
+            .filter(|name| self.font(name).is_none())
+            .map(|name| format!("font '{}'", name));
+        let atlases = ATLASES
+            .iter()
+            .filter(|name| self.atlas(name).is_none())
+            .map(|name| format!("atlas '{}'", name));
+        let sounds = SOUNDS
+            .iter()
+            .filter(|name| self.sound(name).is_none())
+            .map(|name| format!("sound '{}'", name));
+        fonts.chain(atlases).chain(sounds).collect()
+    }
+}
+
+impl RonAsset for AssetManifest {
+    const EXTENSIONS: &'static [&'static str] = &["manifest.ron"];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_the_missing_names() {
+        let manifest: AssetManifest = ron::de::from_str(
+            r#"(
+                textures: {},
+                atlases: {},
+                fonts: { "text": "fonts/text.ttf" },
+                sounds: { "hit": [], "paddle": ["sounds/paddle.mp3"] },
+            )"#,
+        )
+        .unwrap();
+
+        let missing = manifest.missing();
+        assert!(!missing.contains(&"font 'text'".to_string()));
+        assert!(missing.contains(&"atlas 'bricks'".to_string()));
+        // a bank without files is as good as missing
+        assert!(missing.contains(&"sound 'hit'".to_string()));
+        assert!(!missing.contains(&"sound 'paddle'".to_string()));
+        assert!(missing.contains(&"sound 'laser'".to_string()));
+    }
+
+    #[test]
+    fn shipped_themes_are_complete() {
+        for path in std::fs::read_dir("assets/themes").unwrap() {
+            let path = path.unwrap().path();
+            let manifest: AssetManifest =
+                ron::de::from_str(&std::fs::read_to_string(&path).unwrap())
+                    .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+            assert!(manifest.missing().is_empty(), "{}", path.display());
+        }
+    }
+}
@@ -1,24 +1,37 @@
-use crate::{loading::FontAssets, GameState};
-use bevy::{app::AppExit, prelude::*};
+use crate::{
+    game::run::start_run,
+    loading::LevelAssets,
+    localization::{Locale, Localized},
+    GameState,
+};
+use bevy::{
+    app::AppExit,
+    ecs::{component::Component, system::EntityCommands},
+    prelude::*,
+};
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.init_resource::<ButtonMaterials>()
+            .init_resource::<MenuInput>()
+            .add_system(menu_navigation.system().label("menu_navigation"))
             .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(setup_menu.system()))
             .add_system_set(
                 SystemSet::on_update(GameState::Menu)
-                    .with_system(button_interaction.system())
-                    .with_system(handle_keyboard.system()),
+                    .after("menu_navigation")
+                    .with_system(menu_actions.system()),
             )
             .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(cleanup.system()));
     }
 }
 
-struct ButtonMaterials {
-    normal: Handle<ColorMaterial>,
-    hovered: Handle<ColorMaterial>,
+pub struct ButtonMaterials {
+    pub normal: Handle<ColorMaterial>,
+    pub hovered: Handle<ColorMaterial>,
+    /// the invisible node laying the buttons out
+    pub layout: Handle<ColorMaterial>,
 }
 
 impl FromWorld for ButtonMaterials {
@@ -29,89 +42,128 @@ impl FromWorld for ButtonMaterials {
         ButtonMaterials {
             normal: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
             hovered: materials.add(Color::rgb(0.25, 0.25, 0.25).into()),
+            layout: materials.add(Color::NONE.into()),
         }
     }
 }
 
+struct MenuScreen;
+
 enum MenuButton {
     Play,
+    Stats,
+    Options,
+    Achievements,
     Exit,
 }
 
-fn setup_menu(
-    mut commands: Commands,
-    font_assets: Res<FontAssets>,
-    windows: Res<Windows>,
-    button_materials: Res<ButtonMaterials>,
-) {
-    let button_size = Size::new(Val::Px(160.0), Val::Px(50.0));
-    let (top, left) = if let Some(window) = windows.get_primary() {
-        (window.height() / 2.0, window.width() / 2.0 - 80.0)
-    } else {
-        (300.0, 100.0)
-    };
+fn setup_menu(mut commands: Commands, locale: Res<Locale>, button_materials: Res<ButtonMaterials>) {
+    MenuBuilder::new(Size::new(Val::Px(250.0), Val::Px(50.0)))
+        .button(MenuButton::Play, "menu.play")
+        .button(MenuButton::Stats, "menu.stats")
+        .button(MenuButton::Options, "menu.options")
+        .button(MenuButton::Achievements, "menu.achievements")
+        .button(MenuButton::Exit, "menu.exit")
+        .spawn(&mut commands, &locale, &button_materials)
+        .insert(MenuScreen);
+}
+
+/// A column of buttons in the middle of the window, navigable with the mouse, keyboard or gamepad
+pub struct MenuBuilder<T> {
+    button_size: Size<Val>,
+    style: Style,
+    buttons: Vec<(T, Localized)>,
+}
 
-    commands
-        .spawn_bundle(ButtonBundle {
+impl<T: Component> MenuBuilder<T> {
+    pub fn new(button_size: Size<Val>) -> Self {
+        MenuBuilder {
+            button_size,
             style: Style {
-                size: button_size,
-                // margin: Rect::all(Val::Auto),
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Px(top - 60.0),
-                    left: Val::Px(left),
-                    ..Default::default()
-                },
                 ..Default::default()
             },
-            material: button_materials.normal.clone(),
+            buttons: vec![],
+        }
+    }
+
+    /// Replaces the full window layout, e.g. to place the menu inside another node
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Adds a button below the previous ones, `button` is the component identifying it
+    pub fn button(mut self, button: T, label: impl Into<Localized>) -> Self {
+        self.buttons.push((button, label.into()));
+        self
+    }
+
+    /// Spawns the menu with the first button focused, returning its root to insert the screen marker
+    pub fn spawn<'a, 'b>(
+        self,
+        commands: &'b mut Commands<'a>,
+        locale: &Locale,
+        button_materials: &ButtonMaterials,
+    ) -> EntityCommands<'a, 'b> {
+        let button_size = self.button_size;
+        let buttons: Vec<Entity> = self
+            .buttons
+            .into_iter()
+            .map(|(button, label)| {
+                spawn_button(
+                    commands,
+                    locale,
+                    &button_materials.normal,
+                    button_size,
+                    label,
+                )
+                .insert(button)
+                .id()
+            })
+            .collect();
+
+        let mut menu = commands.spawn_bundle(NodeBundle {
+            style: self.style,
+            material: button_materials.layout.clone(),
             ..Default::default()
-        })
-        .insert(MenuButton::Play)
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text {
-                    sections: vec![TextSection {
-                        value: "Play".to_string(),
-                        style: TextStyle {
-                            font: font_assets.text_font.clone(),
-                            font_size: 40.0,
-                            color: Color::rgb(0.9, 0.9, 0.9),
-                        },
-                    }],
-                    alignment: Default::default(),
-                },
-                ..Default::default()
-            });
         });
-    commands
-        .spawn_bundle(ButtonBundle {
-            style: Style {
-                size: button_size,
-                // margin: Rect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Px(top + 10.0),
-                    left: Val::Px(left),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            material: button_materials.normal.clone(),
+        menu.push_children(&buttons).insert(Menu { focus: 0 });
+        menu
+    }
+}
+
+/// Spawns a button with a text label, returning it to insert the component that identifies it
+fn spawn_button<'a, 'b>(
+    commands: &'b mut Commands<'a>,
+    locale: &Locale,
+    material: &Handle<ColorMaterial>,
+    size: Size<Val>,
+    label: Localized,
+) -> EntityCommands<'a, 'b> {
+    let mut button = commands.spawn_bundle(ButtonBundle {
+        style: Style {
+            size,
+            margin: Rect::all(Val::Px(10.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
             ..Default::default()
-        })
-        .insert(MenuButton::Exit)
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
+        },
+        material: material.clone(),
+        ..Default::default()
+    });
+    button.with_children(|parent| {
+        parent
+            .spawn_bundle(TextBundle {
                 text: Text {
                     sections: vec![TextSection {
-                        value: "Exit".to_string(),
+                        value: locale.text(&label),
                         style: TextStyle {
-                            font: font_assets.text_font.clone(),
+                            font: locale.font.clone(),
                             font_size: 40.0,
                             color: Color::rgb(0.9, 0.9, 0.9),
                         },
@@ -119,46 +171,135 @@ fn setup_menu(
                     alignment: Default::default(),
                 },
                 ..Default::default()
-            });
-        });
+            })
+            .insert(label);
+    });
+    button
 }
 
-type ButtonInteraction<'a> = (
-    &'a Interaction,
-    &'a mut Handle<ColorMaterial>,
-    &'a MenuButton,
-);
+/// Root of a menu spawned by `MenuBuilder`, its children are the buttons in order
+pub struct Menu {
+    /// index of the highlighted button
+    focus: usize,
+}
 
-fn handle_keyboard(keyboard_input: Res<Input<KeyCode>>, mut app_exit_events: EventWriter<AppExit>) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
-        app_exit_events.send(AppExit);
+/// What was done in the menu on screen this frame, the same from the mouse, keyboard or gamepad
+#[derive(Default)]
+pub struct MenuInput {
+    confirmed: Option<Entity>,
+    back: Option<Entity>,
+}
+
+impl MenuInput {
+    /// The button that was clicked or confirmed
+    pub fn confirmed(&self) -> Option<Entity> {
+        self.confirmed
+    }
+
+    /// The root of the menu the player backed out of, with escape or the gamepad's east button
+    pub fn back(&self) -> Option<Entity> {
+        self.back
     }
 }
 
-fn button_interaction(
+fn menu_navigation(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
     button_materials: Res<ButtonMaterials>,
+    mut menu_input: ResMut<MenuInput>,
+    mut menu_query: Query<(Entity, &mut Menu, &Children)>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut material_query: Query<&mut Handle<ColorMaterial>, With<Button>>,
+) {
+    *menu_input = MenuInput::default();
+    let (entity, mut menu, buttons) = match menu_query.iter_mut().next() {
+        Some(menu) if !menu.2.is_empty() => menu,
+        _ => return,
+    };
+
+    let pressed = |keys: &[KeyCode], gamepad_button: GamepadButtonType| {
+        keys.iter().any(|key| keyboard_input.just_pressed(*key))
+            || gamepad_input
+                .get_just_pressed()
+                .any(|GamepadButton(_gamepad, button)| *button == gamepad_button)
+    };
+
+    let count = buttons.len();
+    if pressed(&[KeyCode::Up, KeyCode::W], GamepadButtonType::DPadUp) {
+        menu.focus = (menu.focus + count - 1) % count;
+    }
+    if pressed(&[KeyCode::Down, KeyCode::S], GamepadButtonType::DPadDown) {
+        menu.focus = (menu.focus + 1) % count;
+    }
+    if pressed(&[KeyCode::Return], GamepadButtonType::South) {
+        menu_input.confirmed = Some(buttons[menu.focus]);
+    }
+    if pressed(&[KeyCode::Escape], GamepadButtonType::East) {
+        menu_input.back = Some(entity);
+    }
+
+    // the mouse moves the focus too, so there's only ever one button highlighted
+    for (index, button) in buttons.iter().enumerate() {
+        match interaction_query.get(*button) {
+            Ok(Interaction::Hovered) => menu.focus = index,
+            Ok(Interaction::Clicked) => {
+                menu.focus = index;
+                menu_input.confirmed = Some(*button);
+            }
+            _ => {}
+        }
+    }
+
+    for (index, button) in buttons.iter().enumerate() {
+        if let Ok(mut material) = material_query.get_mut(*button) {
+            let highlight = if index == menu.focus {
+                &button_materials.hovered
+            } else {
+                &button_materials.normal
+            };
+            if *material != *highlight {
+                *material = highlight.clone();
+            }
+        }
+    }
+}
+
+fn menu_actions(
+    mut commands: Commands,
+    menu_input: Res<MenuInput>,
     mut state: ResMut<State<GameState>>,
-    mut interaction_query: Query<ButtonInteraction, (Changed<Interaction>, With<MenuButton>)>,
+    mut level_assets: ResMut<LevelAssets>,
     mut app_exit_events: EventWriter<AppExit>,
+    screen_query: Query<&MenuScreen>,
+    button_query: Query<&MenuButton>,
 ) {
-    for (interaction, mut material, menu_button) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Clicked => match menu_button {
-                MenuButton::Play => state.set(GameState::Game).expect("state: menu -> game"),
-                MenuButton::Exit => app_exit_events.send(AppExit),
-            },
-            Interaction::Hovered => {
-                *material = button_materials.hovered.clone();
-            }
-            Interaction::None => {
-                *material = button_materials.normal.clone();
+    if let Some(Ok(_screen)) = menu_input.back().map(|menu| screen_query.get(menu)) {
+        app_exit_events.send(AppExit);
+    }
+
+    if let Some(Ok(button)) = menu_input
+        .confirmed()
+        .map(|button| button_query.get(button))
+    {
+        match button {
+            MenuButton::Play => {
+                start_run(&mut commands, &mut level_assets, rand::random());
+                state.set(GameState::Game).expect("state: menu -> game");
             }
+            MenuButton::Stats => state.set(GameState::Stats).expect("state: menu -> stats"),
+            MenuButton::Options => state
+                .set(GameState::Options)
+                .expect("state: menu -> options"),
+            MenuButton::Achievements => state
+                .set(GameState::Achievements)
+                .expect("state: menu -> achievements"),
+            MenuButton::Exit => app_exit_events.send(AppExit),
         }
     }
 }
 
-fn cleanup(mut commands: Commands, buttons: Query<(Entity, &MenuButton)>) {
-    for (entity, _button) in buttons.iter() {
+fn cleanup(mut commands: Commands, screen_query: Query<(Entity, &MenuScreen)>) {
+    for (entity, _screen) in screen_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
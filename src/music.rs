@@ -0,0 +1,250 @@
+use crate::{
+    game::level::Level,
+    loading::{LevelAssets, MusicAssets},
+    GameState,
+};
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel};
+
+/// Plays the music track of each part of the game, crossfading from one to the next
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Music {
+            channels: [
+                AudioChannel::new("music-a".to_owned()),
+                AudioChannel::new("music-b".to_owned()),
+            ],
+            current: 0,
+            track: None,
+            levels: [0.0, 0.0],
+            duck: 1.0,
+            volumes: [0.0, 0.0],
+            layers: Vec::new(),
+            intensity: 0.0,
+            playback_rate: 1.0,
+        });
+        app.init_resource::<MusicIntensity>();
+
+        app.add_system_set(SystemSet::on_exit(GameState::Loading).with_system(reset.system()));
+        app.add_system(pick_track.system().label("music"));
+        app.add_system(crossfade.system().after("music"));
+    }
+}
+
+const MUSIC_VOLUME: f32 = 0.7;
+const CROSSFADE_SECONDS: f32 = 1.5;
+/// volume of the music, relative to the usual one, while the game is paused
+const DUCKED_VOLUME: f32 = 0.35;
+/// seconds for the music to follow a change of intensity
+const INTENSITY_SECONDS: f32 = 2.0;
+/// intensity over the threshold of a layer for it to reach full volume
+const LAYER_RAMP: f32 = 0.15;
+
+/// How heated the game is, from 0.0 to 1.0, set by the game.
+/// The stems of the game music fade in and it speeds up as it rises.
+#[derive(Default)]
+pub struct MusicIntensity(pub f32);
+
+/// Two channels so the new track fades in while the previous one fades out
+struct Music {
+    channels: [AudioChannel; 2],
+    /// channel of the track that is fading in or playing
+    current: usize,
+    track: Option<String>,
+    /// fade of each channel from 0.0 to 1.0
+    levels: [f32; 2],
+    duck: f32,
+    /// last volume set on each channel
+    volumes: [f32; 2],
+    /// stems of the game music, only playing along it
+    layers: Vec<Layer>,
+    /// follows `MusicIntensity` smoothly
+    intensity: f32,
+    playback_rate: f32,
+}
+
+struct Layer {
+    channel: AudioChannel,
+    /// intensity at which it starts to fade in
+    threshold: f32,
+    level: f32,
+    volume: f32,
+}
+
+/// Stops the music of the previous theme when the assets are reloaded
+fn reset(audio: Res<Audio>, mut music: ResMut<Music>) {
+    for channel in music.channels.iter() {
+        audio.stop_channel(channel);
+    }
+    stop_layers(&audio, &mut music);
+    music.track = None;
+    music.levels = [0.0, 0.0];
+    music.volumes = [0.0, 0.0];
+}
+
+/// Starts the track of the current state on the other channel, `crossfade` does the rest
+fn pick_track(
+    audio: Res<Audio>,
+    state: Res<State<GameState>>,
+    music_assets: Option<Res<MusicAssets>>,
+    level_assets: Option<Res<LevelAssets>>,
+    levels: Res<Assets<Level>>,
+    mut music: ResMut<Music>,
+) {
+    let music_assets = match music_assets {
+        Some(music_assets) => music_assets,
+        None => return,
+    };
+    let cues = &music_assets.cues;
+    let (track, jingle) = match state.current() {
+        // keeps playing the previous track while reloading a theme
+        GameState::Loading | GameState::Pause => return,
+        GameState::Menu
+        | GameState::Options
+        | GameState::Achievements
+        | GameState::Profiles
+        | GameState::Stats => (Some(&cues.menu), false),
+        GameState::Game => {
+            let level = level_assets
+                .as_ref()
+                .and_then(|level_assets| levels.get(level_assets.current()));
+            let track = level
+                .and_then(|level| level.music.as_ref())
+                .unwrap_or(&cues.game);
+            (Some(track), false)
+        }
+        GameState::GameOver => (cues.game_over.as_ref(), true),
+        GameState::LevelComplete => (cues.level_complete.as_ref(), true),
+    };
+    let track = match track {
+        Some(track) if music.track.as_ref() != Some(track) => track,
+        _ => return,
+    };
+
+    let current = 1 - music.current;
+    music.current = current;
+    music.track = Some(track.clone());
+    music.playback_rate = 1.0;
+    let channel = &music.channels[current];
+    audio.stop_channel(channel);
+    // the tempo is set again by `crossfade` when it's the game music
+    audio.set_playback_rate_in_channel(1.0, channel);
+    if let Some(source) = music_assets.track(track) {
+        audio.set_volume_in_channel(0.0, channel);
+        if jingle {
+            audio.play_in_channel(source, channel);
+        } else {
+            audio.play_looped_in_channel(source, channel);
+        }
+    }
+    music.levels[current] = 0.0;
+    music.volumes[current] = 0.0;
+
+    stop_layers(&audio, &mut music);
+    if *track == cues.game {
+        start_layers(&audio, &music_assets, &mut music);
+    }
+}
+
+/// Starts the stems of the game music, silent until the intensity rises
+fn start_layers(audio: &Audio, music_assets: &MusicAssets, music: &mut Music) {
+    for (index, layer) in music_assets.cues.game_layers.iter().enumerate() {
+        if let Some(source) = music_assets.track(&layer.track) {
+            let channel = AudioChannel::new(format!("music-layer-{}", index));
+            audio.set_volume_in_channel(0.0, &channel);
+            audio.set_playback_rate_in_channel(music.playback_rate, &channel);
+            audio.play_looped_in_channel(source, &channel);
+            music.layers.push(Layer {
+                channel,
+                threshold: layer.intensity,
+                level: 0.0,
+                volume: 0.0,
+            });
+        }
+    }
+}
+
+fn stop_layers(audio: &Audio, music: &mut Music) {
+    for layer in music.layers.drain(..) {
+        audio.stop_channel(&layer.channel);
+    }
+}
+
+/// Fades the current channel in and the other one out, lowering both while paused.
+/// Also fades the layers of the game music and sets its tempo by the intensity.
+fn crossfade(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    state: Res<State<GameState>>,
+    music_assets: Option<Res<MusicAssets>>,
+    intensity: Res<MusicIntensity>,
+    mut music: ResMut<Music>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_SECONDS;
+    let duck = if *state.current() == GameState::Pause {
+        DUCKED_VOLUME
+    } else {
+        1.0
+    };
+    music.duck = approach(music.duck, duck, step);
+
+    let music = &mut *music;
+    for index in 0..music.channels.len() {
+        let target = if index == music.current { 1.0 } else { 0.0 };
+        music.levels[index] = approach(music.levels[index], target, step);
+
+        let volume = MUSIC_VOLUME * music.levels[index] * music.duck;
+        if (volume - music.volumes[index]).abs() > f32::EPSILON {
+            music.volumes[index] = volume;
+            if volume > 0.0 {
+                audio.set_volume_in_channel(volume, &music.channels[index]);
+            } else {
+                audio.stop_channel(&music.channels[index]);
+            }
+        }
+    }
+
+    music.intensity = approach(
+        music.intensity,
+        intensity.0,
+        time.delta_seconds() / INTENSITY_SECONDS,
+    );
+    let fade = music.levels[music.current] * music.duck;
+    for layer in music.layers.iter_mut() {
+        let target = ((music.intensity - layer.threshold) / LAYER_RAMP).clamp(0.0, 1.0);
+        layer.level = approach(layer.level, target, step);
+
+        // stays playing when silent, to keep in time with the rest of the music
+        let volume = MUSIC_VOLUME * layer.level * fade;
+        if (volume - layer.volume).abs() > f32::EPSILON {
+            layer.volume = volume;
+            audio.set_volume_in_channel(volume, &layer.channel);
+        }
+    }
+
+    // only the game music and its layers change tempo
+    let tempo_boost = match &music_assets {
+        Some(music_assets) if music.track.as_ref() == Some(&music_assets.cues.game) => {
+            music_assets.cues.tempo_boost
+        }
+        _ => 0.0,
+    };
+    let playback_rate = 1.0 + music.intensity * tempo_boost;
+    if (playback_rate - music.playback_rate).abs() > 0.002 {
+        music.playback_rate = playback_rate;
+        audio.set_playback_rate_in_channel(playback_rate, &music.channels[music.current]);
+        for layer in music.layers.iter() {
+            audio.set_playback_rate_in_channel(playback_rate, &layer.channel);
+        }
+    }
+}
+
+fn approach(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{
+    loading::{SelectedTheme, THEMES},
+    localization::{Locale, Localized, LANGUAGES},
+    menu::{ButtonMaterials, MenuBuilder, MenuInput},
+    settings::{
+        self, BrickLabels, BrickPalette, DisplayMode, Settings, BRICK_LABELS, BRICK_PALETTES,
+        DISPLAY_MODES, FRAME_CAPS, GAME_SPEEDS, MSAA_SAMPLES, RESOLUTION_SCALES, TEXT_SCALES,
+    },
+    GameState,
+};
+use bevy::prelude::*;
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<OptionsPage>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Options).with_system(setup_options.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Options)
+                    .after("menu_navigation")
+                    .with_system(options_actions.system())
+                    .with_system(update_labels.system()),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Options).with_system(cleanup.system()));
+    }
+}
+
+struct OptionsScreen;
+
+/// The options are split in pages, so each fits on screen
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OptionsPage {
+    Main,
+    Accessibility,
+    Assists,
+}
+
+impl Default for OptionsPage {
+    fn default() -> Self {
+        OptionsPage::Main
+    }
+}
+
+#[derive(Clone, Copy)]
+enum OptionsButton {
+    Theme,
+    DisplayMode,
+    ResolutionScale,
+    Vsync,
+    Msaa,
+    FrameCap,
+    Language,
+    Accessibility,
+    Assists,
+    AudioCues,
+    BrickPalette,
+    BrickLabels,
+    HighContrast,
+    TextScale,
+    ReducedMotion,
+    GameSpeed,
+    AutoAim,
+    SafetyNet,
+    LandingIndicator,
+    Back,
+}
+
+impl OptionsButton {
+    fn label(&self, theme: &SelectedTheme, settings: &Settings) -> Localized {
+        let on_off = |enabled| if enabled { "common.on" } else { "common.off" };
+        let percent = |value: f32| format!("{:.0}", value * 100.0);
+        match self {
+            OptionsButton::Theme => {
+                Localized::from("options.theme").arg("value", THEMES[theme.0].0.to_string())
+            }
+            OptionsButton::DisplayMode => Localized::from("options.display_mode").arg(
+                "value",
+                match settings.display_mode {
+                    DisplayMode::Windowed => "display_mode.windowed",
+                    DisplayMode::Borderless => "display_mode.borderless",
+                    DisplayMode::Fullscreen => "display_mode.fullscreen",
+                },
+            ),
+            OptionsButton::ResolutionScale => Localized::from("options.resolution_scale")
+                .arg("value", percent(settings.resolution_scale)),
+            OptionsButton::Vsync => {
+                Localized::from("options.vsync").arg("value", on_off(settings.vsync))
+            }
+            // the render pipelines only pick it up on the next run
+            OptionsButton::Msaa => {
+                Localized::from("options.msaa").arg("value", settings.msaa_samples.to_string())
+            }
+            OptionsButton::FrameCap => Localized::from("options.frame_cap").arg(
+                "value",
+                match settings.frame_cap {
+                    Some(cap) => cap.to_string().into(),
+                    None => Localized::from("options.frame_cap.none"),
+                },
+            ),
+            // the name of the language in itself
+            OptionsButton::Language => {
+                Localized::from("options.language").arg("value", "language.name")
+            }
+            OptionsButton::Accessibility => "options.accessibility".into(),
+            OptionsButton::Assists => "options.assists".into(),
+            OptionsButton::AudioCues => {
+                Localized::from("options.audio_cues").arg("value", on_off(settings.audio_cues))
+            }
+            OptionsButton::BrickPalette => Localized::from("options.brick_palette").arg(
+                "value",
+                match settings.brick_palette {
+                    BrickPalette::Theme => "brick_palette.theme",
+                    BrickPalette::RedGreen => "brick_palette.red_green",
+                    BrickPalette::BlueYellow => "brick_palette.blue_yellow",
+                },
+            ),
+            OptionsButton::BrickLabels => Localized::from("options.brick_labels").arg(
+                "value",
+                match settings.brick_labels {
+                    BrickLabels::Off => "brick_labels.off",
+                    BrickLabels::Numbers => "brick_labels.numbers",
+                    BrickLabels::Patterns => "brick_labels.patterns",
+                },
+            ),
+            OptionsButton::HighContrast => Localized::from("options.high_contrast")
+                .arg("value", on_off(settings.high_contrast)),
+            OptionsButton::TextScale => {
+                Localized::from("options.text_scale").arg("value", percent(settings.text_scale))
+            }
+            OptionsButton::ReducedMotion => Localized::from("options.reduced_motion")
+                .arg("value", on_off(settings.reduced_motion)),
+            OptionsButton::GameSpeed => {
+                Localized::from("options.game_speed").arg("value", percent(settings.game_speed))
+            }
+            OptionsButton::AutoAim => {
+                Localized::from("options.auto_aim").arg("value", on_off(settings.auto_aim))
+            }
+            OptionsButton::SafetyNet => {
+                Localized::from("options.safety_net").arg("value", on_off(settings.safety_net))
+            }
+            OptionsButton::LandingIndicator => Localized::from("options.landing_indicator")
+                .arg("value", on_off(settings.landing_indicator)),
+            OptionsButton::Back => "common.back".into(),
+        }
+    }
+}
+
+impl OptionsPage {
+    fn buttons(self) -> Vec<OptionsButton> {
+        match self {
+            OptionsPage::Main => vec![
+                OptionsButton::Theme,
+                OptionsButton::DisplayMode,
+                OptionsButton::ResolutionScale,
+                OptionsButton::Vsync,
+                OptionsButton::Msaa,
+                OptionsButton::FrameCap,
+                OptionsButton::Language,
+                OptionsButton::Accessibility,
+                OptionsButton::Assists,
+                OptionsButton::Back,
+            ],
+            OptionsPage::Accessibility => vec![
+                OptionsButton::AudioCues,
+                OptionsButton::BrickPalette,
+                OptionsButton::BrickLabels,
+                OptionsButton::HighContrast,
+                OptionsButton::TextScale,
+                OptionsButton::ReducedMotion,
+                OptionsButton::Back,
+            ],
+            OptionsPage::Assists => vec![
+                OptionsButton::GameSpeed,
+                OptionsButton::AutoAim,
+                OptionsButton::SafetyNet,
+                OptionsButton::LandingIndicator,
+                OptionsButton::Back,
+            ],
+        }
+    }
+
+    fn spawn(
+        self,
+        commands: &mut Commands,
+        locale: &Locale,
+        button_materials: &ButtonMaterials,
+        theme: &SelectedTheme,
+        settings: &Settings,
+    ) {
+        self.buttons()
+            .into_iter()
+            .fold(
+                MenuBuilder::new(Size::new(Val::Px(500.0), Val::Px(50.0))),
+                |menu, button| {
+                    let label = button.label(theme, settings);
+                    menu.button(button, label)
+                },
+            )
+            .spawn(commands, locale, button_materials)
+            .insert(OptionsScreen);
+    }
+}
+
+fn setup_options(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    button_materials: Res<ButtonMaterials>,
+    theme: Res<SelectedTheme>,
+    settings: Res<Settings>,
+    mut page: ResMut<OptionsPage>,
+) {
+    *page = OptionsPage::Main;
+    page.spawn(&mut commands, &locale, &button_materials, &theme, &settings);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn options_actions(
+    mut commands: Commands,
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    mut theme: ResMut<SelectedTheme>,
+    mut settings: ResMut<Settings>,
+    mut page: ResMut<OptionsPage>,
+    locale: Res<Locale>,
+    button_materials: Res<ButtonMaterials>,
+    screen_query: Query<Entity, With<OptionsScreen>>,
+    button_query: Query<&OptionsButton>,
+) {
+    let back = menu_input
+        .back()
+        .map_or(false, |menu| screen_query.get(menu).is_ok());
+    let confirmed = menu_input
+        .confirmed()
+        .and_then(|button| button_query.get(button).ok())
+        .copied();
+
+    let next_page = match confirmed {
+        Some(OptionsButton::Accessibility) => Some(OptionsPage::Accessibility),
+        Some(OptionsButton::Assists) => Some(OptionsPage::Assists),
+        Some(OptionsButton::Back) => None,
+        Some(button) => {
+            change_setting(button, &mut state, &mut theme, &mut settings);
+            return;
+        }
+        None if back => None,
+        None => return,
+    };
+
+    // back from a page goes to the main one, and from there to the menu
+    let next_page = match next_page {
+        Some(next_page) => next_page,
+        None if *page == OptionsPage::Main => {
+            state.set(GameState::Menu).expect("state: options -> menu");
+            return;
+        }
+        None => OptionsPage::Main,
+    };
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    *page = next_page;
+    page.spawn(&mut commands, &locale, &button_materials, &theme, &settings);
+}
+
+/// Cycles the setting of the button and saves it, the theme is applied by loading its assets again
+fn change_setting(
+    button: OptionsButton,
+    state: &mut State<GameState>,
+    theme: &mut SelectedTheme,
+    settings: &mut Settings,
+) {
+    match button {
+        OptionsButton::Theme => {
+            theme.0 = (theme.0 + 1) % THEMES.len();
+            state
+                .set(GameState::Loading)
+                .expect("state: options -> loading");
+            return;
+        }
+        OptionsButton::DisplayMode => {
+            settings.display_mode = settings::next(&DISPLAY_MODES, settings.display_mode);
+        }
+        OptionsButton::ResolutionScale => {
+            settings.resolution_scale =
+                settings::next(&RESOLUTION_SCALES, settings.resolution_scale);
+        }
+        OptionsButton::Vsync => settings.vsync = !settings.vsync,
+        OptionsButton::Msaa => {
+            settings.msaa_samples = settings::next(&MSAA_SAMPLES, settings.msaa_samples);
+        }
+        OptionsButton::FrameCap => {
+            settings.frame_cap = settings::next(&FRAME_CAPS, settings.frame_cap);
+        }
+        OptionsButton::Language => {
+            let codes: Vec<&str> = LANGUAGES.iter().map(|(code, _)| *code).collect();
+            settings.language = settings::next(&codes, settings.language.as_str()).to_string();
+        }
+        OptionsButton::AudioCues => settings.audio_cues = !settings.audio_cues,
+        OptionsButton::BrickPalette => {
+            settings.brick_palette = settings::next(&BRICK_PALETTES, settings.brick_palette);
+        }
+        OptionsButton::BrickLabels => {
+            settings.brick_labels = settings::next(&BRICK_LABELS, settings.brick_labels);
+        }
+        OptionsButton::HighContrast => settings.high_contrast = !settings.high_contrast,
+        OptionsButton::TextScale => {
+            settings.text_scale = settings::next(&TEXT_SCALES, settings.text_scale);
+        }
+        OptionsButton::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+        OptionsButton::GameSpeed => {
+            settings.game_speed = settings::next(&GAME_SPEEDS, settings.game_speed);
+        }
+        OptionsButton::AutoAim => settings.auto_aim = !settings.auto_aim,
+        OptionsButton::SafetyNet => settings.safety_net = !settings.safety_net,
+        OptionsButton::LandingIndicator => {
+            settings.landing_indicator = !settings.landing_indicator;
+        }
+        // they change the page, see `options_actions`
+        OptionsButton::Accessibility | OptionsButton::Assists | OptionsButton::Back => return,
+    }
+    settings.save();
+}
+
+fn update_labels(
+    theme: Res<SelectedTheme>,
+    settings: Res<Settings>,
+    button_query: Query<(&OptionsButton, &Children)>,
+    mut label_query: Query<&mut Localized>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (button, children) in button_query.iter() {
+        for child in children.iter() {
+            if let Ok(mut label) = label_query.get_mut(*child) {
+                *label = button.label(&theme, &settings);
+            }
+        }
+    }
+}
+
+fn cleanup(mut commands: Commands, screen_query: Query<(Entity, &OptionsScreen)>) {
+    for (entity, _screen) in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
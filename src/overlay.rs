@@ -0,0 +1,245 @@
+use crate::{
+    localization::{Locale, Localized},
+    menu::{ButtonMaterials, Menu, MenuBuilder},
+    settings::Settings,
+};
+use bevy::{
+    ecs::{component::Component, system::EntityCommands},
+    prelude::*,
+};
+
+/// Fades the overlays in and out
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(fade_overlays.system());
+    }
+}
+
+const FADE_SECONDS: f32 = 0.25;
+
+const BACKGROUND: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+
+/// Panel shown over the game, e.g. when it's paused or over
+pub struct Overlay {
+    /// its own, so it can fade without affecting the other overlays
+    background: Handle<ColorMaterial>,
+    menu: Option<Entity>,
+    fade: Timer,
+    closing: bool,
+}
+
+impl Overlay {
+    /// Fades the overlay out and despawns it, its buttons stop responding right away
+    pub fn close(&mut self, commands: &mut Commands) {
+        if self.closing {
+            return;
+        }
+        self.closing = true;
+        self.fade.reset();
+        if let Some(menu) = self.menu {
+            commands.entity(menu).remove::<Menu>();
+        }
+    }
+}
+
+/// A title, with optionally a subtitle, some stats and buttons underneath
+pub struct OverlayBuilder<T> {
+    title: Localized,
+    subtitle: Option<Localized>,
+    stats: Vec<(Localized, Localized)>,
+    menu: MenuBuilder<T>,
+    has_buttons: bool,
+}
+
+impl<T: Component> OverlayBuilder<T> {
+    pub fn new(title: impl Into<Localized>) -> Self {
+        OverlayBuilder {
+            title: title.into(),
+            subtitle: None,
+            stats: vec![],
+            menu: MenuBuilder::new(Size::new(Val::Px(300.0), Val::Px(50.0))).style(Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                margin: Rect::all(Val::Px(10.0)),
+                ..Default::default()
+            }),
+            has_buttons: false,
+        }
+    }
+
+    pub fn subtitle(mut self, subtitle: impl Into<Localized>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Adds a line to the body, with the name on the left and the value on the right
+    pub fn stat(mut self, name: impl Into<Localized>, value: impl Into<Localized>) -> Self {
+        self.stats.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn button(mut self, button: T, label: impl Into<Localized>) -> Self {
+        self.menu = self.menu.button(button, label);
+        self.has_buttons = true;
+        self
+    }
+
+    /// Spawns the overlay fading in, returning it to insert the component that identifies it
+    pub fn spawn<'a, 'b>(
+        self,
+        commands: &'b mut Commands<'a>,
+        locale: &Locale,
+        materials: &mut Assets<ColorMaterial>,
+        button_materials: &ButtonMaterials,
+    ) -> EntityCommands<'a, 'b> {
+        let OverlayBuilder {
+            title,
+            subtitle,
+            stats,
+            menu,
+            has_buttons,
+        } = self;
+        let menu = if has_buttons {
+            Some(menu.spawn(commands, locale, button_materials).id())
+        } else {
+            None
+        };
+
+        let background = materials.add(Color::NONE.into());
+        // transparent until the first fade step
+        let text =
+            |parent: &mut ChildBuilder, value: &Localized, font_size: f32, mut color: Color| {
+                let bundle = TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(5.0)),
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        locale.text(value),
+                        TextStyle {
+                            font: locale.font.clone(),
+                            font_size,
+                            color: *color.set_a(0.0),
+                        },
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            vertical: VerticalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                };
+                parent.spawn_bundle(bundle).insert(value.clone());
+            };
+
+        let mut overlay = commands.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Auto, Val::Auto),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Percent(15.0),
+                    left: Val::Percent(10.0),
+                    right: Val::Percent(10.0),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: background.clone(),
+            ..Default::default()
+        });
+        overlay.with_children(|parent| {
+            text(parent, &title, 72.0, Color::ORANGE);
+            if let Some(subtitle) = &subtitle {
+                text(parent, subtitle, 30.0, Color::WHITE);
+            }
+            for (name, value) in stats.iter() {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(560.0), Val::Auto),
+                            justify_content: JustifyContent::SpaceBetween,
+                            ..Default::default()
+                        },
+                        material: button_materials.layout.clone(),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        text(parent, name, 24.0, Color::WHITE);
+                        text(parent, value, 24.0, Color::WHITE);
+                    });
+            }
+        });
+        if let Some(menu) = menu {
+            overlay.push_children(&[menu]);
+        }
+        overlay.insert(Overlay {
+            background,
+            menu,
+            fade: Timer::from_seconds(FADE_SECONDS, false),
+            closing: false,
+        });
+        overlay
+    }
+}
+
+fn fade_overlays(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut overlay_query: Query<(Entity, &mut Overlay)>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut overlay) in overlay_query.iter_mut() {
+        if overlay.fade.finished() && !overlay.closing {
+            continue;
+        }
+
+        // shown and hidden at once with reduced motion
+        let delta = if settings.reduced_motion {
+            overlay.fade.duration()
+        } else {
+            time.delta()
+        };
+        overlay.fade.tick(delta);
+        let alpha = if overlay.closing {
+            overlay.fade.percent_left()
+        } else {
+            overlay.fade.percent()
+        };
+        if let Some(material) = materials.get_mut(&overlay.background) {
+            let mut color = BACKGROUND;
+            color.set_a(BACKGROUND.a() * alpha);
+            material.color = color;
+        }
+        set_text_alpha(entity, alpha, &children_query, &mut text_query);
+
+        if overlay.closing && overlay.fade.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn set_text_alpha(
+    entity: Entity,
+    alpha: f32,
+    children_query: &Query<&Children>,
+    text_query: &mut Query<&mut Text>,
+) {
+    if let Ok(mut text) = text_query.get_mut(entity) {
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+    if let Ok(children) = children_query.get(entity) {
+        for child in children.iter() {
+            set_text_alpha(*child, alpha, children_query, text_query);
+        }
+    }
+}
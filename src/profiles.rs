@@ -0,0 +1,347 @@
+use crate::{
+    localization::{Locale, Localized},
+    menu::{ButtonMaterials, MenuInput},
+    overlay::OverlayBuilder,
+    GameState,
+};
+use bevy::{ecs::component::Component, prelude::*};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs};
+
+/// Relative to the working directory, like the settings
+const PROFILES_PATH: &str = "profiles.ron";
+/// where profiles that can't be read are moved, instead of being overwritten by the next save
+const BACKUP_PATH: &str = "profiles.ron.bak";
+
+/// Bumped when the saved profiles change in a way `#[serde(default)]` can't cover,
+/// with a step in `Profiles::parse` upgrading the older ones
+const SCHEMA_VERSION: u32 = 1;
+
+/// as many as fit on the profiles screen
+const MAX_PROFILES: usize = 5;
+
+/// Local player profiles, each with its lifetime stats, picked when the game starts
+pub struct ProfilesPlugin;
+
+impl Plugin for ProfilesPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Profiles::load())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Profiles).with_system(setup_profiles.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Profiles)
+                    .after("menu_navigation")
+                    .with_system(profiles_actions.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Profiles)
+                    .with_system(cleanup::<ProfilesScreen>.system()),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Stats).with_system(setup_stats.system()))
+            .add_system_set(
+                SystemSet::on_update(GameState::Stats)
+                    .after("menu_navigation")
+                    .with_system(stats_actions.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Stats).with_system(cleanup::<StatsScreen>.system()),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Profiles {
+    version: u32,
+    /// index in `profiles` of the one playing
+    selected: usize,
+    profiles: Vec<Profile>,
+    /// whether one was picked since the game started, it starts on the profiles screen until then
+    #[serde(skip)]
+    pub picked: bool,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Profiles {
+            version: SCHEMA_VERSION,
+            selected: 0,
+            profiles: vec![],
+            picked: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub stats: LifetimeStats,
+}
+
+/// Totals over every game played with a profile
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub games_played: u32,
+    /// by the hits the bricks took to destroy
+    pub bricks_destroyed: BTreeMap<u32, u32>,
+    pub balls_lost: u32,
+    pub paddle_hits: u32,
+    /// furthest level reached in a game, 1 for the first
+    pub best_level: u32,
+    /// in seconds, not counting pauses
+    pub time_played: f32,
+}
+
+impl Profiles {
+    /// The saved profiles, none when there are none yet or they can't be read
+    pub fn load() -> Self {
+        let saved = match fs::read_to_string(PROFILES_PATH) {
+            Ok(saved) => saved,
+            Err(_) => return Profiles::default(),
+        };
+        match Profiles::parse(&saved) {
+            Ok(profiles) => profiles,
+            Err(error) => {
+                warn!(path = PROFILES_PATH, %error, "failed to read the profiles, moving them aside");
+                if let Err(error) = fs::rename(PROFILES_PATH, BACKUP_PATH) {
+                    warn!(path = BACKUP_PATH, %error, "failed to move the profiles");
+                }
+                Profiles::default()
+            }
+        }
+    }
+
+    fn parse(saved: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+
+        let Version { version } = ron::de::from_str(saved)?;
+        let mut profiles: Profiles = match version {
+            SCHEMA_VERSION => ron::de::from_str(saved)?,
+            _ => anyhow::bail!("unknown profiles version {}", version),
+        };
+        profiles.selected = profiles
+            .selected
+            .min(profiles.profiles.len().saturating_sub(1));
+        Ok(profiles)
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::new())
+            .map_err(anyhow::Error::from)
+            .and_then(|profiles| fs::write(PROFILES_PATH, profiles).map_err(anyhow::Error::from));
+        match result {
+            Ok(()) => debug!(path = PROFILES_PATH, "profiles saved"),
+            Err(error) => warn!(path = PROFILES_PATH, %error, "failed to save profiles"),
+        }
+    }
+
+    /// The profile playing, `None` until one is made
+    pub fn current(&self) -> Option<&Profile> {
+        self.profiles.get(self.selected)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut Profile> {
+        self.profiles.get_mut(self.selected)
+    }
+
+    /// Adds a profile and selects it
+    fn add(&mut self, name: String) {
+        self.profiles.push(Profile {
+            name,
+            stats: LifetimeStats::default(),
+        });
+        self.selected = self.profiles.len() - 1;
+    }
+}
+
+struct ProfilesScreen;
+
+enum ProfilesButton {
+    Select(usize),
+    New,
+}
+
+fn setup_profiles(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut profiles: ResMut<Profiles>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    button_materials: Res<ButtonMaterials>,
+) {
+    if profiles.profiles.is_empty() {
+        profiles.add(locale.text(&default_name(1)));
+    }
+
+    let mut overlay = OverlayBuilder::new("profiles.title");
+    for (index, profile) in profiles.profiles.iter().enumerate() {
+        let label = if index == profiles.selected {
+            Localized::from("profiles.current").arg("name", profile.name.clone())
+        } else {
+            profile.name.clone().into()
+        };
+        overlay = overlay.button(ProfilesButton::Select(index), label);
+    }
+    if profiles.profiles.len() < MAX_PROFILES {
+        overlay = overlay.button(ProfilesButton::New, "profiles.new");
+    }
+    overlay
+        .spawn(&mut commands, &locale, &mut materials, &button_materials)
+        .insert(ProfilesScreen);
+}
+
+fn default_name(number: usize) -> Localized {
+    Localized::from("profiles.default_name").arg("number", number.to_string())
+}
+
+fn profiles_actions(
+    locale: Res<Locale>,
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    mut profiles: ResMut<Profiles>,
+    button_query: Query<&ProfilesButton>,
+) {
+    let confirmed = menu_input
+        .confirmed()
+        .and_then(|button| button_query.get(button).ok());
+    match confirmed {
+        Some(ProfilesButton::Select(index)) => profiles.selected = *index,
+        Some(ProfilesButton::New) => {
+            let name = locale.text(&default_name(profiles.profiles.len() + 1));
+            profiles.add(name);
+        }
+        // backing out keeps the selected one, the only menu on screen is the profiles'
+        None if menu_input.back().is_some() => {}
+        None => return,
+    }
+
+    profiles.picked = true;
+    profiles.save();
+    state.set(GameState::Menu).expect("state: profiles -> menu");
+}
+
+struct StatsScreen;
+
+enum StatsButton {
+    SwitchProfile,
+    Back,
+}
+
+fn setup_stats(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    button_materials: Res<ButtonMaterials>,
+) {
+    let mut overlay = OverlayBuilder::new("lifetime.title");
+    if let Some(profile) = profiles.current() {
+        let stats = &profile.stats;
+        let time_played = stats.time_played as u32;
+        overlay = overlay
+            .subtitle(profile.name.clone())
+            .stat("lifetime.games_played", stats.games_played.to_string())
+            .stat(
+                "lifetime.bricks_destroyed",
+                stats.bricks_destroyed.values().sum::<u32>().to_string(),
+            );
+        for (hits, destroyed) in stats.bricks_destroyed.iter() {
+            overlay = overlay.stat(
+                Localized::from("lifetime.bricks_by_hits").arg("hits", hits.to_string()),
+                destroyed.to_string(),
+            );
+        }
+        overlay = overlay
+            .stat("lifetime.balls_lost", stats.balls_lost.to_string())
+            .stat("lifetime.paddle_hits", stats.paddle_hits.to_string())
+            .stat("lifetime.best_level", stats.best_level.to_string())
+            .stat(
+                "lifetime.time_played",
+                format!(
+                    "{}:{:02}:{:02}",
+                    time_played / 3600,
+                    time_played / 60 % 60,
+                    time_played % 60
+                ),
+            );
+    }
+    overlay
+        .button(StatsButton::SwitchProfile, "lifetime.switch_profile")
+        .button(StatsButton::Back, "common.back")
+        .spawn(&mut commands, &locale, &mut materials, &button_materials)
+        .insert(StatsScreen);
+}
+
+fn stats_actions(
+    menu_input: Res<MenuInput>,
+    mut state: ResMut<State<GameState>>,
+    button_query: Query<&StatsButton>,
+) {
+    let confirmed = menu_input
+        .confirmed()
+        .and_then(|button| button_query.get(button).ok());
+    match confirmed {
+        Some(StatsButton::SwitchProfile) => state
+            .set(GameState::Profiles)
+            .expect("state: stats -> profiles"),
+        Some(StatsButton::Back) => state.set(GameState::Menu).expect("state: stats -> menu"),
+        // the only menu on screen is the stats'
+        None if menu_input.back().is_some() => {
+            state.set(GameState::Menu).expect("state: stats -> menu")
+        }
+        None => {}
+    }
+}
+
+fn cleanup<T: Component>(mut commands: Commands, screen_query: Query<Entity, With<T>>) {
+    for entity in screen_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_the_current_version() {
+        let mut profiles = Profiles::default();
+        profiles.add("First".to_string());
+        profiles.add("Second".to_string());
+        let stats = &mut profiles.current_mut().unwrap().stats;
+        stats.games_played = 3;
+        stats.bricks_destroyed.insert(2, 40);
+        stats.time_played = 125.5;
+
+        let saved = ron::ser::to_string_pretty(&profiles, PrettyConfig::new()).unwrap();
+        let parsed = Profiles::parse(&saved).unwrap();
+
+        assert_eq!(parsed.version, SCHEMA_VERSION);
+        assert_eq!(parsed.selected, 1);
+        assert_eq!(parsed.profiles.len(), 2);
+        let profile = parsed.current().unwrap();
+        assert_eq!(profile.name, "Second");
+        assert_eq!(profile.stats.games_played, 3);
+        assert_eq!(profile.stats.bricks_destroyed.get(&2), Some(&40));
+        assert_eq!(profile.stats.time_played, 125.5);
+        // not saved, the profile is picked again every time the game starts
+        assert!(!parsed.picked);
+    }
+
+    #[test]
+    fn rejects_an_unknown_version() {
+        let saved = "(version: 2, selected: 0, profiles: [])";
+        assert!(Profiles::parse(saved).is_err());
+    }
+
+    #[test]
+    fn rejects_a_file_without_version() {
+        let saved = "(selected: 0, profiles: [(name: \"Player 1\")])";
+        assert!(Profiles::parse(saved).is_err());
+    }
+}
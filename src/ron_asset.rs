@@ -0,0 +1,41 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+/// An asset read from a RON file by `RonAssetLoader`
+pub trait RonAsset: TypeUuid + DeserializeOwned + Send + Sync + 'static {
+    /// e.g. `&["level.ron"]`, so several RON assets can live side by side
+    const EXTENSIONS: &'static [&'static str];
+}
+
+pub struct RonAssetLoader<T> {
+    asset: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for RonAssetLoader<T> {
+    fn default() -> Self {
+        RonAssetLoader { asset: PhantomData }
+    }
+}
+
+impl<T: RonAsset> AssetLoader for RonAssetLoader<T> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let asset = ron::de::from_bytes::<T>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
@@ -0,0 +1,232 @@
+use crate::camera::logical_size;
+use bevy::{app::CoreStage, prelude::*, window::WindowMode};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+/// Kept between runs, relative to the working directory the game is started from
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// Applies the display settings to the primary window as they change
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(apply_display.system())
+            .add_system_to_stage(CoreStage::Last, limit_frame_rate.system());
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum DisplayMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen { use_size: false },
+        }
+    }
+}
+
+/// Colors of the bricks by the hits they have left, see `accessibility`
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum BrickPalette {
+    /// the frames of the theme's brick atlas, as they are
+    Theme,
+    /// tells the bricks apart with protanopia or deuteranopia
+    RedGreen,
+    /// tells the bricks apart with tritanopia
+    BlueYellow,
+}
+
+/// What is drawn over the bricks to show the hits they have left, without relying on color
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub enum BrickLabels {
+    Off,
+    Numbers,
+    /// a dot per hit left
+    Patterns,
+}
+
+pub const DISPLAY_MODES: [DisplayMode; 3] = [
+    DisplayMode::Windowed,
+    DisplayMode::Borderless,
+    DisplayMode::Fullscreen,
+];
+/// size of the window relative to the play area, when windowed
+pub const RESOLUTION_SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+pub const MSAA_SAMPLES: [u32; 2] = [1, 4];
+/// `None` leaves the frame rate uncapped
+pub const FRAME_CAPS: [Option<u32>; 5] = [None, Some(30), Some(60), Some(120), Some(144)];
+pub const BRICK_PALETTES: [BrickPalette; 3] = [
+    BrickPalette::Theme,
+    BrickPalette::RedGreen,
+    BrickPalette::BlueYellow,
+];
+pub const BRICK_LABELS: [BrickLabels; 3] = [
+    BrickLabels::Off,
+    BrickLabels::Numbers,
+    BrickLabels::Patterns,
+];
+/// size of the text relative to the one the game was designed with
+pub const TEXT_SCALES: [f32; 3] = [1.0, 1.25, 1.5];
+/// speed of the game relative to the normal one
+pub const GAME_SPEEDS: [f32; 5] = [0.5, 0.6, 0.75, 0.9, 1.0];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub display_mode: DisplayMode,
+    pub resolution_scale: f32,
+    pub vsync: bool,
+    /// only applied on the next run, the render pipelines are built with it
+    pub msaa_samples: u32,
+    pub frame_cap: Option<u32>,
+    /// sounds telling where the ball is, for players who can't follow it on screen
+    pub audio_cues: bool,
+    pub brick_palette: BrickPalette,
+    pub brick_labels: BrickLabels,
+    /// plain, bright colors for the walls, the ball, the paddle and the lasers
+    pub high_contrast: bool,
+    pub text_scale: f32,
+    /// no fades or flying debris
+    pub reduced_motion: bool,
+    /// the assists below, and a game speed under 1.0, are flagged on the score
+    pub game_speed: f32,
+    /// nudges the paddle toward where the ball will land
+    pub auto_aim: bool,
+    /// saves the first ball to fall in each level
+    pub safety_net: bool,
+    pub landing_indicator: bool,
+    /// code of one of `localization::LANGUAGES`
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            display_mode: DisplayMode::Windowed,
+            resolution_scale: 1.0,
+            vsync: false,
+            msaa_samples: 4,
+            frame_cap: None,
+            audio_cues: false,
+            brick_palette: BrickPalette::Theme,
+            brick_labels: BrickLabels::Off,
+            high_contrast: false,
+            text_scale: 1.0,
+            reduced_motion: false,
+            game_speed: 1.0,
+            auto_aim: false,
+            safety_net: false,
+            landing_indicator: false,
+            language: "en".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// The saved settings, the defaults when there are none yet or they can't be read
+    pub fn load() -> Self {
+        fs::read_to_string(SETTINGS_PATH)
+            .ok()
+            .and_then(|settings| ron::de::from_str(&settings).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::new())
+            .map_err(anyhow::Error::from)
+            .and_then(|settings| fs::write(SETTINGS_PATH, settings).map_err(anyhow::Error::from));
+        match result {
+            Ok(()) => debug!(path = SETTINGS_PATH, "settings saved"),
+            Err(error) => warn!(path = SETTINGS_PATH, %error, "failed to save settings"),
+        }
+    }
+
+    /// Whether any assist is on, the score of a run played with one is flagged
+    pub fn assisted(&self) -> bool {
+        self.game_speed < 1.0 || self.auto_aim || self.safety_net || self.landing_indicator
+    }
+
+    /// The window as configured before the app starts
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        let (width, height) = self.window_size();
+        WindowDescriptor {
+            title: "Breakout".to_string(),
+            width,
+            height,
+            // the play area is scaled to whatever size the window ends up with
+            resizable: true,
+            vsync: self.vsync,
+            mode: self.display_mode.window_mode(),
+            ..Default::default()
+        }
+    }
+
+    fn window_size(&self) -> (f32, f32) {
+        let size = logical_size() * self.resolution_scale;
+        (size.x, size.y)
+    }
+}
+
+/// Cycles through `values`, starting over after the last one
+pub fn next<T: PartialEq + Copy>(values: &[T], current: T) -> T {
+    let index = values
+        .iter()
+        .position(|value| *value == current)
+        .map_or(0, |index| (index + 1) % values.len());
+    values[index]
+}
+
+/// The window is only touched when one of its own settings changed,
+/// not on every change to the settings
+fn apply_display(
+    settings: Res<Settings>,
+    mut windows: ResMut<Windows>,
+    mut applied: Local<Option<(DisplayMode, bool, f32)>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let display = (
+        settings.display_mode,
+        settings.vsync,
+        settings.resolution_scale,
+    );
+    if *applied == Some(display) {
+        return;
+    }
+    *applied = Some(display);
+
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_mode(settings.display_mode.window_mode());
+        window.set_vsync(settings.vsync);
+        if settings.display_mode == DisplayMode::Windowed {
+            let (width, height) = settings.window_size();
+            window.set_resolution(width, height);
+        }
+    }
+}
+
+/// Sleeps out the rest of the frame when it took less than the cap allows
+fn limit_frame_rate(settings: Res<Settings>, mut last_frame: Local<Option<Instant>>) {
+    if let (Some(cap), Some(last_frame)) = (settings.frame_cap, *last_frame) {
+        let frame_time = Duration::from_secs_f64(1.0 / cap as f64);
+        let elapsed = last_frame.elapsed();
+        if elapsed < frame_time {
+            std::thread::sleep(frame_time - elapsed);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}